@@ -0,0 +1,769 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::channel::frequency_from_channel;
+use crate::{normalize_mac, Band, Channel, Security, Wifi};
+
+/// Returns the hotspots you can likely connect to, i.e. those that aren't hidden and
+/// have a signal of at least `min_dbm` (e.g. `-80`).
+///
+/// Enterprise networks need a username and password rather than just a key, so are
+/// only included when `include_enterprise` is set.
+pub fn connectable(wifis: &[Wifi], min_dbm: i32, include_enterprise: bool) -> Vec<&Wifi> {
+    wifis
+        .iter()
+        .filter(|wifi| !wifi.is_hidden())
+        .filter(|wifi| wifi.signal_dbm().is_some_and(|dbm| dbm >= min_dbm))
+        .filter(|wifi| include_enterprise || wifi.security_type() != Security::Enterprise)
+        .collect()
+}
+
+/// Returns the hotspots on `band`, dropping those whose band is unknown.
+///
+/// Like `connectable`, this borrows from `wifis` rather than cloning the hotspots, so
+/// the results can't outlive the slice. Call `.into_iter().cloned().collect()` on them
+/// to keep owned copies.
+pub fn filter_by_band(wifis: &[Wifi], band: Band) -> Vec<&Wifi> {
+    wifis
+        .iter()
+        .filter(|wifi| {
+            wifi.channel_info()
+                .is_some_and(|channel| channel.band == band)
+        })
+        .collect()
+}
+
+/// Returns the hotspots whose `security_type` is `security`, borrowing them from
+/// `wifis` like `filter_by_band`.
+pub fn filter_by_security(wifis: &[Wifi], security: Security) -> Vec<&Wifi> {
+    wifis
+        .iter()
+        .filter(|wifi| wifi.security_type() == security)
+        .collect()
+}
+
+/// Groups the hotspots by primary channel number, sorted by channel, e.g. to chart
+/// how busy each channel is. Hotspots keep the order they were found in within a
+/// channel, and those whose channel couldn't be parsed are skipped.
+///
+/// 6 GHz channels reuse the numbers of the other bands, so use `filter_by_band` first
+/// when they should be charted separately.
+pub fn by_channel(wifis: &[Wifi]) -> BTreeMap<u32, Vec<Wifi>> {
+    let mut channels: BTreeMap<u32, Vec<Wifi>> = BTreeMap::new();
+    for wifi in wifis {
+        if let Some(channel) = wifi.channel_info() {
+            channels
+                .entry(channel.number)
+                .or_default()
+                .push(wifi.clone());
+        }
+    }
+    channels
+}
+
+/// Estimates how busy each primary channel is, from `0.0` (idle) to `1.0`
+/// (saturated), e.g. to pick the quietest channel for a new access point.
+///
+/// This is a heuristic blending three signals per channel:
+///
+/// - utilization, the highest `channel_utilization` a hotspot on the channel reports
+///   (its access point measured the airtime in use), weighted 0.6
+/// - clients, the total `station_count` of its hotspots, saturating at 50, weighted 0.2
+/// - density, the number of hotspots on it, saturating at 10, weighted 0.2
+///
+/// Utilization is only reported by hotspots advertising their BSS load (currently only
+/// parsed from `iw` on Linux). When none on a channel does, clients and density are
+/// weighted 0.5 each instead. Channels are grouped like `by_channel`.
+pub fn channel_load_estimate(wifis: &[Wifi]) -> BTreeMap<u32, f32> {
+    const SATURATING_STATIONS: f32 = 50.0;
+    const SATURATING_NETWORKS: f32 = 10.0;
+
+    by_channel(wifis)
+        .into_iter()
+        .map(|(channel, wifis)| {
+            let utilization = wifis
+                .iter()
+                .filter_map(|wifi| wifi.channel_utilization)
+                .max()
+                .map(|utilization| f32::from(utilization) / 255.0);
+            let stations: u32 = wifis
+                .iter()
+                .filter_map(|wifi| wifi.station_count)
+                .map(u32::from)
+                .sum();
+            let clients = (stations as f32 / SATURATING_STATIONS).min(1.0);
+            let density = (wifis.len() as f32 / SATURATING_NETWORKS).min(1.0);
+
+            let load = match utilization {
+                Some(utilization) => 0.6 * utilization + 0.2 * clients + 0.2 * density,
+                None => 0.5 * clients + 0.5 * density,
+            };
+            (channel, load.min(1.0))
+        })
+        .collect()
+}
+
+/// Returns a fingerprint of the access points found, which only changes when one
+/// appears, disappears or changes channel, e.g. for a daemon polling often to cheaply
+/// tell whether anything changed before diffing the scans.
+///
+/// Only the mac address (normalized to lowercase) and the `channel` of each hotspot
+/// contribute, every other field, including the signal, is ignored. The order of the
+/// hotspots and hotspots reported more than once don't matter either. The fingerprint is
+/// a 64-bit FNV-1a hash, the same across runs, platforms and versions of this crate,
+/// so it can be stored.
+pub fn scan_fingerprint(wifis: &[Wifi]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+    let access_points: BTreeSet<(String, &str)> = wifis
+        .iter()
+        .map(|wifi| (normalize_mac(&wifi.mac), wifi.channel.trim()))
+        .collect();
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for (mac, channel) in &access_points {
+        // the separators keep e.g. `ab` `c` and `a` `bc` apart
+        for &byte in mac
+            .as_bytes()
+            .iter()
+            .chain(b"\0")
+            .chain(channel.as_bytes())
+            .chain(b"\n")
+        {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
+
+/// Returns the names of the networks found, without duplicates and sorted, e.g. to
+/// offer a list of networks to join. Hidden networks are left out.
+pub fn ssids(wifis: &[Wifi]) -> Vec<String> {
+    wifis
+        .iter()
+        .filter(|wifi| !wifi.is_hidden())
+        .map(|wifi| wifi.ssid.as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Returns the access point (BSSID) broadcasting `ssid` with the strongest signal, e.g.
+/// to pick which one to connect or roam to, or `None` if no hotspot has that exact SSID.
+///
+/// Ties on the signal in whole dBm go to the lowest mac address, so the same scan
+/// always gives the same answer. Hotspots whose signal couldn't be parsed are only
+/// returned when no other hotspot matches.
+pub fn best_bssid_for<'a>(ssid: &str, wifis: &'a [Wifi]) -> Option<&'a Wifi> {
+    wifis
+        .iter()
+        .filter(|wifi| wifi.ssid == ssid)
+        .min_by_key(|wifi| (Reverse(wifi.signal_dbm()), normalize_mac(&wifi.mac)))
+}
+
+/// Returns the SSIDs broadcast on more than one band (e.g. both 2.4 and 5 GHz), sorted
+/// by name. These hotspots usually steer clients to the band with the better signal.
+///
+/// The band comes from the `frequency` when reported, otherwise from the channel
+/// number. Hidden networks and hotspots whose band is unknown are ignored.
+pub fn band_steered_ssids(wifis: &[Wifi]) -> Vec<String> {
+    let mut bands: BTreeMap<&str, BTreeSet<Band>> = BTreeMap::new();
+    for wifi in wifis.iter().filter(|wifi| !wifi.is_hidden()) {
+        let band = match wifi.frequency {
+            Some(frequency) => Band::from_frequency(frequency),
+            None => wifi.channel_info().map(|channel| channel.band),
+        };
+        if let Some(band) = band {
+            bands.entry(&wifi.ssid).or_default().insert(band);
+        }
+    }
+
+    bands
+        .into_iter()
+        .filter(|(_, bands)| bands.len() > 1)
+        .map(|(ssid, _)| ssid.to_string())
+        .collect()
+}
+
+/// Returns the SSID and channel pairs on which more than one access point (BSSID)
+/// broadcasts the same SSID, sorted by SSID and then channel. Access points of a healthy
+/// mesh or multi-AP network use different channels, so these are likely misconfigured
+/// and slow each other down.
+///
+/// Hidden networks and hotspots whose channel can't be parsed are ignored. Channels are
+/// told apart by band, but reported by number only, like `by_channel`.
+pub fn same_channel_duplicate_ssids(wifis: &[Wifi]) -> Vec<(String, u32)> {
+    let mut bssids: BTreeMap<(&str, Band, u32), BTreeSet<String>> = BTreeMap::new();
+    for wifi in wifis.iter().filter(|wifi| !wifi.is_hidden()) {
+        if let Some(channel) = wifi.channel_info() {
+            bssids
+                .entry((&wifi.ssid, channel.band, channel.number))
+                .or_default()
+                .insert(normalize_mac(&wifi.mac));
+        }
+    }
+
+    bssids
+        .into_iter()
+        .filter(|(_, bssids)| bssids.len() > 1)
+        .map(|((ssid, _, number), _)| (ssid.to_string(), number))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Returns the SSIDs broadcast by both open and secured hotspots, along with all of the
+/// hotspots broadcasting them in the order they were found, sorted by SSID. An open
+/// clone of a secured network is a classic evil twin, set up to catch clients that
+/// connect to it automatically.
+///
+/// Expect false positives: guest networks often run an open hotspot with the same SSID
+/// as the secured one, e.g. with a captive portal, so check the mac addresses of the
+/// open hotspots against the access points you know. Hidden networks and hotspots
+/// whose security isn't recognised by `Security::parse` are ignored.
+pub fn open_clones_of_secured(wifis: &[Wifi]) -> Vec<(String, Vec<Wifi>)> {
+    let mut by_ssid: BTreeMap<&str, Vec<&Wifi>> = BTreeMap::new();
+    for wifi in wifis.iter().filter(|wifi| !wifi.is_hidden()) {
+        if wifi.security_type() != Security::Unknown {
+            by_ssid.entry(&wifi.ssid).or_default().push(wifi);
+        }
+    }
+
+    by_ssid
+        .into_iter()
+        .filter(|(_, wifis)| {
+            let open = |wifi: &&Wifi| wifi.security_type() == Security::Open;
+            wifis.iter().any(open) && !wifis.iter().all(open)
+        })
+        .map(|(ssid, wifis)| (ssid.to_string(), wifis.into_iter().cloned().collect()))
+        .collect()
+}
+
+/// Groups the hotspots that are likely radios of the same physical access point, e.g.
+/// to count the routers nearby rather than their networks. Groups are sorted by SSID
+/// and then mac address.
+///
+/// This is a heuristic: dual band access points usually give their radios mac
+/// addresses that only differ by one in the last octet, so hotspots with the same SSID
+/// and such neighbouring mac addresses are grouped (a chain of them for tri-band
+/// access points). Vendors using other schemes end up in separate groups, and two
+/// routers with consecutive mac addresses advertising the same SSID are merged.
+pub fn group_physical_aps(wifis: &[Wifi]) -> Vec<Vec<Wifi>> {
+    let mut sorted: Vec<(&Wifi, String)> = wifis
+        .iter()
+        .map(|wifi| (wifi, crate::normalize_mac(&wifi.mac)))
+        .collect();
+    sorted.sort_by(|(a, a_mac), (b, b_mac)| (&a.ssid, a_mac).cmp(&(&b.ssid, b_mac)));
+
+    let mut groups: Vec<Vec<Wifi>> = Vec::new();
+    let mut previous: Option<(&Wifi, String)> = None;
+    for (wifi, mac) in sorted {
+        match previous {
+            Some((last, ref last_mac))
+                if last.ssid == wifi.ssid && is_adjacent_bssid(last_mac, &mac) =>
+            {
+                groups.last_mut().unwrap().push(wifi.clone())
+            }
+            _ => groups.push(vec![wifi.clone()]),
+        }
+        previous = Some((wifi, mac));
+    }
+    groups
+}
+
+/// Returns the pairs of WiFi 6 hotspots using the same BSS color on overlapping
+/// channels, in the order they were found. Clients of either hotspot then can't tell
+/// their frames apart, so one of them should be given another color.
+///
+/// Channels overlap when they're on the same band and, on 2.4 GHz, less than five
+/// channels apart, or otherwise share the same primary channel. Wider channels with
+/// different primary channels aren't checked. Hotspots of one access point may share a
+/// color on purpose, so also check `group_physical_aps` before acting on a conflict.
+pub fn color_conflicts(wifis: &[Wifi]) -> Vec<(Wifi, Wifi)> {
+    let colored: Vec<(&Wifi, u8, Channel)> = wifis
+        .iter()
+        .filter_map(|wifi| Some((wifi, wifi.bss_color?, wifi.channel_info()?)))
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for (index, (a, a_color, a_channel)) in colored.iter().enumerate() {
+        for (b, b_color, b_channel) in &colored[index + 1..] {
+            if a_color == b_color && channels_overlap(a_channel, b_channel) {
+                conflicts.push(((*a).clone(), (*b).clone()));
+            }
+        }
+    }
+    conflicts
+}
+
+fn channels_overlap(a: &Channel, b: &Channel) -> bool {
+    a.band == b.band
+        && match a.band {
+            Band::TwoPointFourGhz => a.number.abs_diff(b.number) < 5,
+            _ => a.number == b.number,
+        }
+}
+
+/// Returns how much of the spectrum two hotspots share, from `0.0` (none) to `1.0`
+/// (the narrower channel lies entirely within the other), e.g. to score channel plans.
+///
+/// Co-channel hotspots fully overlap, 2.4 GHz channels 1 and 3 half overlap and
+/// channels 1 and 6 don't overlap at all. Channels are taken as 20 MHz wide unless a
+/// wider width is reported, in which case the 40 MHz secondary channel's position
+/// and the standard 80, 160 and 320 MHz channel blocks are used. Widths other than
+/// these are taken as unknown. Hotspots whose channel can't be parsed don't overlap
+/// anything.
+pub fn channel_overlap(a: &Wifi, b: &Wifi) -> f32 {
+    match (spectrum(a), spectrum(b)) {
+        (Some((a_low, a_high)), Some((b_low, b_high))) => {
+            let shared = a_high.min(b_high).saturating_sub(a_low.max(b_low));
+            let narrower = (a_high - a_low).min(b_high - b_low);
+            if narrower == 0 {
+                return 0.0;
+            }
+            shared as f32 / narrower as f32
+        }
+        _ => 0.0,
+    }
+}
+
+/// Returns the lowest and highest frequencies in MHz a hotspot's channel occupies.
+fn spectrum(wifi: &Wifi) -> Option<(u32, u32)> {
+    let channel = wifi.channel_info()?;
+    let frequency = wifi
+        .frequency
+        .or_else(|| frequency_from_channel(channel.band, channel.number))?;
+    // airport reports 40 MHz channels as e.g. `36,+1`
+    let offset = wifi.secondary_channel_offset.or_else(|| {
+        match wifi
+            .channel
+            .split_once(',')
+            .map(|(_, offset)| offset.trim())
+        {
+            Some("+1") => Some(1),
+            Some("-1") => Some(-1),
+            _ => None,
+        }
+    });
+    // e.g. `1,9999` isn't a channel width
+    let reported_width = channel
+        .width_mhz
+        .filter(|width| [20, 40, 80, 160, 320].contains(width));
+    let width = match (reported_width, offset) {
+        (Some(width), _) => u32::from(width),
+        (None, Some(1)) | (None, Some(-1)) => 40,
+        (None, _) => 20,
+    };
+
+    let low = match (width, offset) {
+        (40, Some(-1)) => frequency.checked_sub(30)?,
+        (40, _) => frequency.checked_sub(10)?,
+        (80, _) | (160, _) | (320, _) if channel.band != Band::TwoPointFourGhz => {
+            // wide channels are fixed blocks of 20 MHz channels, the 5 GHz ones start
+            // at channel 36 and again at 149
+            let first = match (channel.band, channel.number) {
+                (Band::SixGhz, _) => 1,
+                (_, number) if number >= 149 => 149,
+                _ => 36,
+            };
+            let step = width / 5;
+            let start = first + channel.number.saturating_sub(first) / step * step;
+            frequency_from_channel(channel.band, start)?.checked_sub(10)?
+        }
+        _ => frequency.checked_sub(width / 2)?,
+    };
+    Some((low, low + width))
+}
+
+/// Returns true if two normalized mac addresses share their first five octets and
+/// their last octets differ by at most one.
+fn is_adjacent_bssid(a: &str, b: &str) -> bool {
+    fn octets(mac: &str) -> Option<Vec<u8>> {
+        let octets = mac
+            .split(':')
+            .map(|octet| u8::from_str_radix(octet, 16).ok())
+            .collect::<Option<Vec<_>>>()?;
+        if octets.len() == 6 {
+            Some(octets)
+        } else {
+            None
+        }
+    }
+
+    match (octets(a), octets(b)) {
+        (Some(a), Some(b)) => a[..5] == b[..5] && a[5].abs_diff(b[5]) <= 1,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wifi(mac: &str, ssid: &str, channel: &str, signal_level: &str, security: &str) -> Wifi {
+        Wifi {
+            mac: mac.to_string(),
+            ssid: ssid.to_string(),
+            channel: channel.to_string(),
+            signal_level: signal_level.to_string(),
+            security: security.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_filter_connectable() {
+        let wifis = [
+            wifi("", "strong", "", "-50", "WPA2-Personal"),
+            wifi("", "weak", "", "-85.00", "PSK"),
+            wifi("", "", "", "-40", "Open"),
+            wifi("", "work", "", "-60", "WPA2-Enterprise"),
+            wifi("", "unknown", "", "", "Open"),
+            wifi("", "edge", "", "-80.00", "NONE"),
+        ];
+
+        assert_eq!(vec![&wifis[0], &wifis[5]], connectable(&wifis, -80, false));
+        assert_eq!(
+            vec![&wifis[0], &wifis[3], &wifis[5]],
+            connectable(&wifis, -80, true)
+        );
+        assert_eq!(vec![&wifis[0]], connectable(&wifis, -55, true));
+    }
+
+    #[test]
+    fn should_filter_by_band_and_security() {
+        let wifis = [
+            wifi("", "", "6", "", "WPA2-Personal"),
+            wifi("", "", "36", "", "Open"),
+            Wifi {
+                frequency: Some(5975),
+                ..wifi("", "", "5", "", "WPA3-Personal")
+            },
+            wifi("", "", "", "", "WPA2-Personal"),
+        ];
+
+        assert_eq!(
+            vec![&wifis[0]],
+            filter_by_band(&wifis, Band::TwoPointFourGhz)
+        );
+        assert_eq!(vec![&wifis[1]], filter_by_band(&wifis, Band::FiveGhz));
+        assert_eq!(vec![&wifis[2]], filter_by_band(&wifis, Band::SixGhz));
+        assert_eq!(
+            vec![&wifis[0], &wifis[3]],
+            filter_by_security(&wifis, Security::Personal)
+        );
+    }
+
+    #[test]
+    fn should_group_by_channel() {
+        let wifis = [
+            wifi("", "a", "11", "", ""),
+            wifi("", "b", "1", "", ""),
+            wifi("", "c", "36,+1", "", ""),
+            wifi("", "d", "11", "", ""),
+            wifi("", "e", "", "", ""),
+        ];
+
+        let channels = by_channel(&wifis);
+        assert_eq!(vec![&1, &11, &36], channels.keys().collect::<Vec<_>>());
+        assert_eq!(vec![wifis[1].clone()], channels[&1]);
+        assert_eq!(vec![wifis[0].clone(), wifis[3].clone()], channels[&11]);
+        assert_eq!(vec![wifis[2].clone()], channels[&36]);
+    }
+
+    #[test]
+    fn should_estimate_channel_load() {
+        let busy = Wifi {
+            channel_utilization: Some(255),
+            station_count: Some(5),
+            ..wifi("", "", "11", "", "")
+        };
+        let mut wifis = vec![
+            Wifi {
+                channel_utilization: Some(51),
+                station_count: Some(10),
+                ..wifi("", "", "1", "", "")
+            },
+            Wifi {
+                channel_utilization: Some(102),
+                station_count: Some(15),
+                ..wifi("", "", "1", "", "")
+            },
+            wifi("", "", "6", "", ""),
+        ];
+        wifis.extend((0..12).map(|_| busy.clone()));
+
+        let load = channel_load_estimate(&wifis);
+        assert_eq!(vec![&1, &6, &11], load.keys().collect::<Vec<_>>());
+        // 0.6 * 0.4 utilization + 0.2 * 0.5 clients + 0.2 * 0.2 density
+        assert!((load[&1] - 0.38).abs() < 1e-6);
+        assert!((load[&6] - 0.05).abs() < 1e-6);
+        assert_eq!(1.0, load[&11]);
+    }
+
+    #[test]
+    fn should_find_same_channel_duplicate_ssids() {
+        let wifis = [
+            wifi("00:00:00:00:00:01", "mesh", "1", "", ""),
+            wifi("00:00:00:00:00:02", "mesh", "6", "", ""),
+            wifi("00:00:00:00:00:03", "mesh", "11", "", ""),
+            wifi("00:00:00:00:00:04", "office", "36", "", ""),
+            wifi("00:00:00:00:00:05", "office", "36,+1", "", ""),
+            wifi("00:00:00:00:00:06", "office", "36", "", ""),
+            wifi("00:00:00:00:00:07", "cafe", "6", "", ""),
+            wifi("00:00:00:00:00:08", "cafe", "6", "", ""),
+            // the same BSSID reported twice, e.g. by two interfaces
+            wifi("aa:bb:cc:dd:ee:09", "home", "1", "", ""),
+            wifi("AA:BB:CC:DD:EE:09", "home", "1", "", ""),
+            wifi("00:00:00:00:00:0a", "", "1", "", ""),
+            wifi("00:00:00:00:00:0b", "", "1", "", ""),
+        ];
+
+        assert_eq!(
+            vec![("cafe".to_string(), 6), ("office".to_string(), 36)],
+            same_channel_duplicate_ssids(&wifis)
+        );
+    }
+
+    #[test]
+    fn should_find_open_clones_of_secured() {
+        let wifis = [
+            wifi("", "home", "", "-50", "WPA2-Personal"),
+            wifi("", "cafe", "", "-60", "Open"),
+            wifi("", "home", "", "-40", "Open"),
+            wifi("", "office", "", "-70", "WPA2-Enterprise"),
+            wifi("", "office", "", "-75", "WPA3-Personal"),
+            wifi("", "guest", "", "-65", ""),
+            wifi("", "guest", "", "-66", "WEP"),
+            // not recognised, so neither open nor secured
+            wifi("", "lab", "", "-80", "Open"),
+            wifi("", "lab", "", "-81", "Unknown"),
+            wifi("", "", "", "-55", "Open"),
+            wifi("", "", "", "-56", "WPA2-Personal"),
+        ];
+
+        let clones = open_clones_of_secured(&wifis);
+        assert_eq!(
+            vec!["guest", "home"],
+            clones
+                .iter()
+                .map(|(ssid, _)| ssid.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec![wifis[5].clone(), wifis[6].clone()], clones[0].1);
+        assert_eq!(vec![wifis[0].clone(), wifis[2].clone()], clones[1].1);
+    }
+
+    #[test]
+    fn should_find_best_bssid_for_ssid() {
+        let wifis = [
+            wifi("00:00:00:00:00:03", "office", "", "-70", ""),
+            wifi("00:00:00:00:00:02", "office", "", "-55.00", ""),
+            wifi("00:00:00:00:00:09", "Office", "", "-30", ""),
+            wifi("00:00:00:00:00:01", "office", "", "-55", ""),
+            wifi("00:00:00:00:00:04", "office", "", "", ""),
+            wifi("00:00:00:00:00:05", "guest", "", "", ""),
+        ];
+
+        // tied with :02, but the lower mac address wins
+        assert_eq!(Some(&wifis[3]), best_bssid_for("office", &wifis));
+        assert_eq!(Some(&wifis[5]), best_bssid_for("guest", &wifis));
+        assert_eq!(None, best_bssid_for("home", &wifis));
+    }
+
+    #[test]
+    fn should_fingerprint_access_points() {
+        let wifis = [
+            wifi("00:11:22:33:44:55", "", "1", "-50", ""),
+            wifi("66:77:88:99:aa:bb", "", "36", "-70", ""),
+        ];
+        let fingerprint = scan_fingerprint(&wifis);
+
+        // signal fluctuation, order and letter case don't matter
+        let moved = [
+            wifi("66:77:88:99:AA:BB", "", "36", "-75", ""),
+            wifi("00:11:22:33:44:55", "", "1", "-48.00", ""),
+        ];
+        assert_eq!(fingerprint, scan_fingerprint(&moved));
+
+        let changed_channel = [
+            wifis[0].clone(),
+            Wifi {
+                channel: "40".to_string(),
+                ..wifis[1].clone()
+            },
+        ];
+        assert_ne!(fingerprint, scan_fingerprint(&changed_channel));
+        assert_ne!(fingerprint, scan_fingerprint(&wifis[..1]));
+        // the FNV-1a offset basis, fingerprints mustn't change between versions
+        assert_eq!(0xcbf2_9ce4_8422_2325, scan_fingerprint(&[]));
+    }
+
+    #[test]
+    fn should_list_unique_ssids() {
+        let wifis = [
+            wifi("", "work", "", "-60", "WPA2-Enterprise"),
+            wifi("", "home", "", "-50", "WPA2-Personal"),
+            wifi("", "", "", "-40", "Open"),
+            wifi("", "work", "", "-70", "WPA2-Enterprise"),
+            wifi("", "\\x00\\x00", "", "-70", "Open"),
+        ];
+
+        assert_eq!(vec!["home", "work"], ssids(&wifis));
+    }
+
+    #[test]
+    fn should_find_band_steered_ssids() {
+        let wifis = [
+            wifi("", "home", "6", "", ""),
+            wifi("", "home", "36", "", ""),
+            Wifi {
+                frequency: Some(2412),
+                ..wifi("", "office", "1", "", "")
+            },
+            Wifi {
+                frequency: Some(5955),
+                ..wifi("", "office", "1", "", "")
+            },
+            wifi("", "cafe", "11", "", ""),
+            wifi("", "cafe", "1", "", ""),
+            wifi("", "", "6", "", ""),
+            wifi("", "", "149", "", ""),
+            wifi("", "unknown", "6", "", ""),
+            wifi("", "unknown", "", "", ""),
+        ];
+
+        assert_eq!(
+            vec!["home".to_string(), "office".to_string()],
+            band_steered_ssids(&wifis)
+        );
+    }
+
+    #[test]
+    fn should_find_color_conflicts() {
+        let wifis = [
+            Wifi {
+                bss_color: Some(5),
+                ..wifi("", "", "1", "", "")
+            },
+            Wifi {
+                bss_color: Some(5),
+                ..wifi("", "", "4", "", "")
+            },
+            Wifi {
+                bss_color: Some(5),
+                ..wifi("", "", "6", "", "")
+            },
+            Wifi {
+                bss_color: Some(5),
+                ..wifi("", "", "36", "", "")
+            },
+            Wifi {
+                bss_color: Some(7),
+                ..wifi("", "", "36", "", "")
+            },
+            Wifi {
+                bss_color: Some(5),
+                ..wifi("", "", "36", "", "")
+            },
+            wifi("", "", "36", "", ""),
+        ];
+
+        assert_eq!(
+            vec![
+                (wifis[0].clone(), wifis[1].clone()),
+                (wifis[1].clone(), wifis[2].clone()),
+                (wifis[3].clone(), wifis[5].clone()),
+            ],
+            color_conflicts(&wifis)
+        );
+    }
+
+    #[test]
+    fn should_measure_channel_overlap() {
+        let on = |channel| wifi("", "", channel, "", "");
+
+        assert_eq!(1.0, channel_overlap(&on("1"), &on("1")));
+        assert_eq!(0.5, channel_overlap(&on("1"), &on("3")));
+        assert_eq!(0.0, channel_overlap(&on("1"), &on("6")));
+        assert_eq!(0.0, channel_overlap(&on("36"), &on("40")));
+        assert_eq!(0.0, channel_overlap(&on("1"), &on("")));
+
+        // 40 MHz channels, by `airport` and by `iw`
+        assert_eq!(1.0, channel_overlap(&on("36,+1"), &on("40")));
+        assert_eq!(0.0, channel_overlap(&on("40,-1"), &on("44")));
+        let below = Wifi {
+            secondary_channel_offset: Some(-1),
+            ..on("5")
+        };
+        assert_eq!(1.0, channel_overlap(&below, &on("1")));
+        assert_eq!(0.5, channel_overlap(&below, &on("7")));
+
+        // 80 MHz blocks, 36 to 48 and 149 to 161
+        assert_eq!(1.0, channel_overlap(&on("44,80"), &on("36")));
+        assert_eq!(0.0, channel_overlap(&on("44,80"), &on("52")));
+        assert_eq!(1.0, channel_overlap(&on("157,80"), &on("149")));
+        assert_eq!(0.0, channel_overlap(&on("157,80"), &on("165")));
+
+        // channel 1 on 2.4 and 6 GHz
+        let six_ghz = Wifi {
+            frequency: Some(5955),
+            ..on("1")
+        };
+        assert_eq!(0.0, channel_overlap(&six_ghz, &on("1")));
+    }
+
+    #[test]
+    fn should_take_invalid_channel_widths_as_unknown() {
+        let on = |channel| wifi("", "", channel, "", "");
+
+        assert_eq!(Some((2402, 2422)), spectrum(&on("1,9999")));
+        assert_eq!(1.0, channel_overlap(&on("1,9999"), &on("1")));
+        assert_eq!(Some((2427, 2447)), spectrum(&on("6,0")));
+        assert_eq!(1.0, channel_overlap(&on("6,0"), &on("6,0")));
+        assert_eq!(0.5, channel_overlap(&on("6,0"), &on("8")));
+
+        // a bogus frequency below the channel's lowest one
+        let bogus = Wifi {
+            frequency: Some(5),
+            ..on("1")
+        };
+        assert_eq!(None, spectrum(&bogus));
+        assert_eq!(0.0, channel_overlap(&bogus, &on("1")));
+    }
+
+    #[test]
+    fn should_detect_adjacent_bssids() {
+        assert!(is_adjacent_bssid("00:11:22:33:44:50", "00:11:22:33:44:51"));
+        assert!(is_adjacent_bssid("00:11:22:33:44:51", "00:11:22:33:44:50"));
+        assert!(is_adjacent_bssid("00:11:22:33:44:50", "00:11:22:33:44:50"));
+        assert!(!is_adjacent_bssid("00:11:22:33:44:50", "00:11:22:33:44:52"));
+        assert!(!is_adjacent_bssid("00:11:22:33:44:ff", "00:11:22:33:45:00"));
+        assert!(!is_adjacent_bssid("00:11:22:33:44:50", "not a mac"));
+    }
+
+    #[test]
+    fn should_group_physical_aps() {
+        let wifis = [
+            wifi("00:11:22:33:44:51", "home", "", "", ""),
+            wifi("aa:bb:cc:dd:ee:01", "cafe", "", "", ""),
+            wifi("00:11:22:33:44:50", "home", "", "", ""),
+            wifi("00:11:22:33:44:52", "home", "", "", ""),
+            wifi("00:11:22:33:44:54", "home", "", "", ""),
+            wifi("aa:bb:cc:dd:ee:02", "other", "", "", ""),
+        ];
+
+        assert_eq!(
+            vec![
+                vec![wifis[1].clone()],
+                vec![wifis[2].clone(), wifis[0].clone(), wifis[3].clone()],
+                vec![wifis[4].clone()],
+                vec![wifis[5].clone()],
+            ],
+            group_physical_aps(&wifis)
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use std::process::Command;
+use std::sync::atomic::AtomicBool;
+
+use crate::channel::channel_from_frequency;
+use crate::security::{canonical_security, parse_supplicant_flags};
+use crate::{Error, Result, Wifi};
+
+/// Lists the hotspots an Android device connected over USB or `adb connect` last saw,
+/// with `adb shell cmd wifi list-scan-results`.
+///
+/// Needs `adb` from the Android SDK platform tools on the `PATH` and Android 11 or
+/// later on the device, which has to have USB debugging enabled. `serial` picks the
+/// device as listed by `adb devices` when more than one is connected.
+///
+/// Fails with `Error::CommandNotFound` if `adb` isn't installed and
+/// `Error::CommandFailed` if no device is connected or it doesn't know the command.
+pub fn scan_android(serial: Option<&str>) -> Result<Vec<Wifi>> {
+    let mut command = Command::new("adb");
+    if let Some(serial) = serial {
+        command.args(["-s", serial]);
+    }
+    command.args(["shell", "cmd", "wifi", "list-scan-results"]);
+
+    let output = crate::sys::output(&mut command, &AtomicBool::new(false))?;
+    if !output.status.success() {
+        return Err(Error::CommandFailed(
+            output.status,
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    parse_scan_results(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parses the whitespace aligned `BSSID / Frequency / RSSI / Age(sec) / SSID / Flags`
+/// lines, skipping the header. SSIDs may contain spaces and hidden networks have none,
+/// so the SSID is whatever is left between the age and the flags.
+fn parse_scan_results(scan_results: &str) -> Result<Vec<Wifi>> {
+    let mut wifis = Vec::new();
+
+    for line in scan_results
+        .lines()
+        .skip_while(|line| !line.contains("BSSID"))
+        .skip(1)
+    {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = |reason: &str| Error::Parse {
+            line: line.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let mut rest = line;
+        let mut fields = [""; 4];
+        for field in fields.iter_mut() {
+            let (value, tail) = rest
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| invalid("missing column"))?;
+            *field = value;
+            rest = tail.trim_start();
+        }
+        let [mac, frequency, signal_level, age] = fields;
+        let (ssid, flags) = match rest.rfind(char::is_whitespace) {
+            Some(i) if rest[i..].trim_start().starts_with('[') => (&rest[..i], &rest[i..]),
+            _ if rest.starts_with('[') => ("", rest),
+            _ => (rest, ""),
+        };
+
+        let frequency = frequency
+            .parse()
+            .map_err(|_| invalid("invalid frequency"))?;
+        let age: f64 = age.parse().map_err(|_| invalid("invalid age"))?;
+        let (security, akm_suites) = parse_supplicant_flags(flags.trim());
+        let ssid = ssid.trim_end();
+        wifis.push(Wifi {
+            mac: mac.to_string(),
+            ssid: ssid.to_string(),
+            ssid_raw: ssid.as_bytes().to_vec(),
+            channel: channel_from_frequency(frequency)
+                .map(|channel| channel.to_string())
+                .unwrap_or_default(),
+            signal_level: signal_level.to_string(),
+            security: canonical_security(&security),
+            frequency: Some(frequency),
+            last_seen_ms_ago: Some((age * 1000.0).round() as u64),
+            akm_suites,
+            ..Default::default()
+        });
+    }
+
+    Ok(wifis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn should_parse_list_scan_results() {
+        let fixture = fs::read_to_string("tests/fixtures/adb/list_scan_results_01.txt").unwrap();
+
+        let result = parse_scan_results(&fixture).unwrap();
+        assert_eq!(5, result.len());
+        assert_eq!(
+            Wifi {
+                mac: "00:11:22:33:44:55".to_string(),
+                ssid: "hello".to_string(),
+                ssid_raw: b"hello".to_vec(),
+                channel: "1".to_string(),
+                signal_level: "-45".to_string(),
+                security: "WPA2-Personal".to_string(),
+                frequency: Some(2412),
+                last_seen_ms_ago: Some(1204),
+                akm_suites: vec!["PSK".to_string()],
+                ..Default::default()
+            },
+            result[0]
+        );
+        assert_eq!("cafe au lait", result[1].ssid);
+        assert_eq!("WPA/WPA2-Personal", result[1].security);
+        assert_eq!("36", result[1].channel);
+        assert_eq!("WPA3-Personal", result[2].security);
+        assert_eq!(Some(12031), result[2].last_seen_ms_ago);
+        assert_eq!("WPA2-Enterprise", result[3].security);
+        assert_eq!(vec!["IEEE 802.1X"], result[3].akm_suites);
+        assert_eq!("Open", result[4].security);
+        assert!(result[4].is_hidden());
+    }
+
+    #[test]
+    fn should_not_parse_invalid_frequency() {
+        let results = "BSSID Frequency RSSI Age(sec) SSID Flags\n\
+                       00:11:22:33:44:55 abc -45 1.204 hello [ESS]";
+        assert!(parse_scan_results(results).is_err());
+    }
+}
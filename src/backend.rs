@@ -0,0 +1,113 @@
+use std::process::Command;
+
+/// A command line tool used to scan, see `check_backend`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tool {
+    /// name of the tool, e.g. `iw`
+    pub name: &'static str,
+    /// whether the tool could be run
+    pub available: bool,
+    /// first line of the tool's version output, `None` if it doesn't report one
+    pub version: Option<String>,
+}
+
+/// The tools the platform's backend scans with, see `check_backend`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendStatus {
+    /// every tool the backend can use, in the order they're tried
+    pub tools: Vec<Tool>,
+}
+
+impl BackendStatus {
+    /// Returns the tool `scan` will use, or `None` if none of them are available.
+    pub fn preferred(&self) -> Option<&Tool> {
+        self.tools.iter().find(|tool| tool.available)
+    }
+
+    /// Returns `true` if at least one tool is available, i.e. `scan` can work.
+    pub fn is_available(&self) -> bool {
+        self.preferred().is_some()
+    }
+}
+
+/// Runs `command` to see if `name` is installed, taking the first line of its output
+/// as the version when it exits successfully.
+pub(crate) fn probe(name: &'static str, command: &mut Command) -> Tool {
+    match command.output() {
+        Ok(output) => Tool {
+            name,
+            available: true,
+            version: if output.status.success() {
+                parse_version(&output.stdout).or_else(|| parse_version(&output.stderr))
+            } else {
+                None
+            },
+        },
+        Err(_) => Tool {
+            name,
+            available: false,
+            version: None,
+        },
+    }
+}
+
+/// Returns the first non-empty line with its whitespace collapsed, e.g. `iwlist`'s
+/// `iwlist  Wireless-Tools version 30` becomes `iwlist Wireless-Tools version 30`.
+fn parse_version(output: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(output)
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .find(|line| !line.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_version() {
+        assert_eq!(
+            Some("iw version 5.19".to_string()),
+            parse_version(b"iw version 5.19\n")
+        );
+        assert_eq!(
+            Some("iwlist Wireless-Tools version 30".to_string()),
+            parse_version(
+                b"\niwlist  Wireless-Tools version 30\n          Compatible with Wireless Extension v11 to v22.\n"
+            )
+        );
+        assert_eq!(None, parse_version(b"\n  \n"));
+    }
+
+    #[test]
+    fn should_prefer_first_available_tool() {
+        let status = BackendStatus {
+            tools: vec![
+                Tool {
+                    name: "iw",
+                    available: false,
+                    version: None,
+                },
+                Tool {
+                    name: "iwlist",
+                    available: true,
+                    version: None,
+                },
+            ],
+        };
+        assert_eq!(Some("iwlist"), status.preferred().map(|tool| tool.name));
+        assert!(status.is_available());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn should_probe_tools() {
+        let tool = probe("echo", Command::new("echo").arg("echo 1.0"));
+        assert!(tool.available);
+        assert_eq!(Some("echo 1.0".to_string()), tool.version);
+
+        let tool = probe("missing", &mut Command::new("wifiscanner-missing-tool"));
+        assert!(!tool.available);
+        assert_eq!(None, tool.version);
+    }
+}
@@ -1,9 +1,19 @@
 fn main() {
-    let networks = wifiscanner::scan().expect("Cannot scan network");
-    for network in networks {
-        println!(
-            "{} {:15} {:10} {:4} {}",
-            network.mac, network.ssid, network.channel, network.signal_level, network.security
-        );
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let anonymize = args.iter().any(|arg| arg == "--anonymize");
+    let verbose = args.iter().any(|arg| arg == "--verbose");
+
+    let mut networks = wifiscanner::scan().expect("Cannot scan network");
+    if anonymize {
+        networks = networks.iter().map(wifiscanner::Wifi::anonymized).collect();
+    }
+    if verbose {
+        let details: Vec<String> = networks
+            .iter()
+            .map(wifiscanner::Wifi::detail_string)
+            .collect();
+        print!("{}", details.join("\n"));
+    } else {
+        print!("{}", wifiscanner::format_table(&networks));
     }
 }
@@ -0,0 +1,328 @@
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+use crate::{Error, Result, Wifi};
+
+/// Version of the record layout, written at the start of every record.
+const VERSION: u8 = 1;
+
+/// Largest record `read_binary` accepts, so a corrupt length can't allocate gigabytes.
+const MAX_RECORD_LEN: u32 = 64 * 1024 * 1024;
+
+/// Appends one scan's hotspots to `writer` as a single binary record, a fraction of the
+/// size of the same scan written as JSON lines. Read records back with `read_binary`.
+///
+/// Records are length-prefixed, so many scans can be appended to the same file, e.g. by
+/// a monitor recording a scan every few seconds. Every field of `Wifi` is kept.
+pub fn write_binary<W: Write>(writer: &mut W, wifis: &[Wifi]) -> Result<()> {
+    let mut record = vec![VERSION];
+    write_varint(&mut record, wifis.len() as u64);
+    for wifi in wifis {
+        encode_wifi(&mut record, wifi);
+    }
+
+    writer.write_all(&(record.len() as u32).to_le_bytes())?;
+    writer.write_all(&record)?;
+    Ok(())
+}
+
+/// Reads the next record written by `write_binary`, or `None` at the end of `reader`.
+///
+/// Fails with `Error::Io` of kind `InvalidData` if the record is corrupt or was written
+/// by a newer version of this crate, and `UnexpectedEof` if it was cut short, e.g. by a
+/// monitor killed while appending.
+pub fn read_binary<R: Read>(reader: &mut R) -> Result<Option<Vec<Wifi>>> {
+    let mut len = [0; 4];
+    match reader.read(&mut len[..1])? {
+        0 => return Ok(None),
+        _ => reader.read_exact(&mut len[1..])?,
+    }
+    let len = u32::from_le_bytes(len);
+    if len > MAX_RECORD_LEN {
+        return Err(invalid_data("record is too long").into());
+    }
+
+    let mut record = vec![0; len as usize];
+    reader.read_exact(&mut record)?;
+    decode_record(&record).map(Some).map_err(Error::from)
+}
+
+fn encode_wifi(out: &mut Vec<u8>, wifi: &Wifi) {
+    write_str(out, &wifi.mac);
+    write_str(out, &wifi.ssid);
+    write_str(out, &wifi.channel);
+    write_str(out, &wifi.signal_level);
+    write_str(out, &wifi.security);
+    write_option(out, wifi.radio_type.as_deref(), write_str);
+    write_option(out, wifi.frequency, |out, value| {
+        write_varint(out, value.into())
+    });
+    write_option(out, wifi.snr_db, |out, value| {
+        write_signed(out, value.into())
+    });
+    write_option(out, wifi.last_seen_ms_ago, write_varint);
+    write_strings(out, &wifi.capabilities);
+    write_bytes(out, &wifi.ssid_raw);
+    write_strings(out, &wifi.akm_suites);
+    write_option(out, wifi.spatial_streams, |out, value| out.push(value));
+    write_option(out, wifi.secondary_channel_offset, |out, value| {
+        write_signed(out, value.into())
+    });
+    write_option(out, wifi.bss_color, |out, value| out.push(value));
+    out.push(wifi.qos_enabled as u8);
+    write_option(out, wifi.station_count, |out, value| {
+        write_varint(out, value.into())
+    });
+    write_option(out, wifi.channel_utilization, |out, value| out.push(value));
+    write_strings(out, &wifi.vendor_ies);
+    write_option(out, wifi.encryption.as_deref(), write_str);
+    write_option(out, wifi.max_rate_mbps, |out, value| {
+        write_varint(out, value.into())
+    });
+    out.push(wifi.connected as u8);
+}
+
+fn decode_record(record: &[u8]) -> io::Result<Vec<Wifi>> {
+    let mut decoder = Decoder(record);
+    if decoder.byte()? != VERSION {
+        return Err(invalid_data("unsupported record version"));
+    }
+
+    let count = decoder.varint()?;
+    // every hotspot takes at least a byte, don't trust a corrupt count
+    let mut wifis = Vec::with_capacity(count.min(record.len() as u64) as usize);
+    for _ in 0..count {
+        wifis.push(decoder.wifi()?);
+    }
+    if !decoder.0.is_empty() {
+        return Err(invalid_data("trailing bytes after the last hotspot"));
+    }
+    Ok(wifis)
+}
+
+/// Reads the values of a record from the front of the slice.
+struct Decoder<'a>(&'a [u8]);
+
+impl<'a> Decoder<'a> {
+    fn wifi(&mut self) -> io::Result<Wifi> {
+        Ok(Wifi {
+            mac: self.string()?,
+            ssid: self.string()?,
+            channel: self.string()?,
+            signal_level: self.string()?,
+            security: self.string()?,
+            radio_type: self.option(Decoder::string)?,
+            frequency: self.option(|decoder| decoder.narrow(Decoder::varint))?,
+            snr_db: self.option(|decoder| decoder.narrow(Decoder::signed))?,
+            last_seen_ms_ago: self.option(Decoder::varint)?,
+            capabilities: self.strings()?,
+            ssid_raw: self.bytes()?.to_vec(),
+            akm_suites: self.strings()?,
+            spatial_streams: self.option(Decoder::byte)?,
+            secondary_channel_offset: self.option(|decoder| decoder.narrow(Decoder::signed))?,
+            bss_color: self.option(Decoder::byte)?,
+            qos_enabled: self.byte()? != 0,
+            station_count: self.option(|decoder| decoder.narrow(Decoder::varint))?,
+            channel_utilization: self.option(Decoder::byte)?,
+            vendor_ies: self.strings()?,
+            encryption: self.option(Decoder::string)?,
+            max_rate_mbps: self.option(|decoder| decoder.narrow(Decoder::varint))?,
+            connected: self.byte()? != 0,
+        })
+    }
+
+    fn byte(&mut self) -> io::Result<u8> {
+        let (&byte, rest) = self
+            .0
+            .split_first()
+            .ok_or_else(|| invalid_data("record ends mid-hotspot"))?;
+        self.0 = rest;
+        Ok(byte)
+    }
+
+    fn bytes(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.varint()?;
+        if len > self.0.len() as u64 {
+            return Err(invalid_data("record ends mid-hotspot"));
+        }
+        let (bytes, rest) = self.0.split_at(len as usize);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| invalid_data("invalid UTF-8"))
+    }
+
+    fn strings(&mut self) -> io::Result<Vec<String>> {
+        (0..self.varint()?).map(|_| self.string()).collect()
+    }
+
+    fn option<T, F>(&mut self, read: F) -> io::Result<Option<T>>
+    where
+        F: FnOnce(&mut Decoder<'a>) -> io::Result<T>,
+    {
+        match self.byte()? {
+            0 => Ok(None),
+            1 => read(self).map(Some),
+            _ => Err(invalid_data("invalid option tag")),
+        }
+    }
+
+    /// LEB128, 7 bits at a time starting with the least significant ones.
+    fn varint(&mut self) -> io::Result<u64> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid_data("varint is too long"))
+    }
+
+    /// Zigzag encoded, so small negative numbers like signal levels stay short.
+    fn signed(&mut self) -> io::Result<i64> {
+        let value = self.varint()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    fn narrow<T, U, F>(&mut self, read: F) -> io::Result<U>
+    where
+        F: FnOnce(&mut Decoder<'a>) -> io::Result<T>,
+        U: TryFrom<T>,
+    {
+        U::try_from(read(self)?).map_err(|_| invalid_data("number out of range"))
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_signed(out: &mut Vec<u8>, value: i64) {
+    write_varint(out, ((value << 1) ^ (value >> 63)) as u64);
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn write_str(out: &mut Vec<u8>, value: &str) {
+    write_bytes(out, value.as_bytes());
+}
+
+fn write_strings(out: &mut Vec<u8>, values: &[String]) {
+    write_varint(out, values.len() as u64);
+    for value in values {
+        write_str(out, value);
+    }
+}
+
+fn write_option<T, F: FnOnce(&mut Vec<u8>, T)>(out: &mut Vec<u8>, value: Option<T>, write: F) {
+    match value {
+        Some(value) => {
+            out.push(1);
+            write(out, value);
+        }
+        None => out.push(0),
+    }
+}
+
+fn invalid_data(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wifis() -> Vec<Wifi> {
+        vec![
+            Wifi {
+                mac: "11:22:33:44:55:66".to_string(),
+                ssid: "café".to_string(),
+                ssid_raw: "café".as_bytes().to_vec(),
+                channel: "36,+1".to_string(),
+                signal_level: "-67.00".to_string(),
+                security: "WPA2-Personal".to_string(),
+                radio_type: Some("802.11ax".to_string()),
+                frequency: Some(5180),
+                snr_db: Some(-3),
+                last_seen_ms_ago: Some(300_000),
+                capabilities: vec!["ESS".to_string(), "Privacy".to_string()],
+                akm_suites: vec!["PSK".to_string(), "IEEE 802.1X".to_string()],
+                spatial_streams: Some(2),
+                secondary_channel_offset: Some(-1),
+                bss_color: Some(63),
+                qos_enabled: true,
+                station_count: Some(300),
+                channel_utilization: Some(255),
+                vendor_ies: vec!["0050f204".to_string()],
+                encryption: Some("CCMP".to_string()),
+                max_rate_mbps: Some(1201),
+                connected: true,
+            },
+            Wifi::default(),
+        ]
+    }
+
+    #[test]
+    fn should_round_trip_records() {
+        let mut log = Vec::new();
+        write_binary(&mut log, &wifis()).unwrap();
+        write_binary(&mut log, &[]).unwrap();
+        write_binary(&mut log, &wifis()[..1]).unwrap();
+
+        let mut reader = &log[..];
+        assert_eq!(Some(wifis()), read_binary(&mut reader).unwrap());
+        assert_eq!(Some(vec![]), read_binary(&mut reader).unwrap());
+        assert_eq!(
+            Some(wifis()[..1].to_vec()),
+            read_binary(&mut reader).unwrap()
+        );
+        assert_eq!(None, read_binary(&mut reader).unwrap());
+    }
+
+    #[test]
+    fn should_reject_truncated_and_corrupt_records() {
+        let mut log = Vec::new();
+        write_binary(&mut log, &wifis()).unwrap();
+
+        let truncated = &log[..log.len() - 1];
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            match read_binary(&mut &truncated[..]) {
+                Err(Error::Io(kind, _)) => kind,
+                result => panic!("unexpected {:?}", result),
+            }
+        );
+
+        let mut corrupt = log.clone();
+        corrupt[4] = VERSION + 1;
+        assert!(matches!(
+            read_binary(&mut &corrupt[..]),
+            Err(Error::Io(io::ErrorKind::InvalidData, _))
+        ));
+    }
+
+    #[test]
+    fn should_round_trip_varints() {
+        for &value in &[0, 1, 127, 128, 300, u64::from(u32::MAX), u64::MAX] {
+            let mut out = Vec::new();
+            write_varint(&mut out, value);
+            assert_eq!(value, Decoder(&out).varint().unwrap());
+        }
+        for &value in &[0, -1, 1, -100, i64::from(i32::MIN), i64::MAX, i64::MIN] {
+            let mut out = Vec::new();
+            write_signed(&mut out, value);
+            assert_eq!(value, Decoder(&out).signed().unwrap());
+        }
+    }
+}
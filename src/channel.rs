@@ -0,0 +1,187 @@
+/// Frequency band a hotspot broadcasts on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Band {
+    /// 2.4 GHz, channels 1 to 14
+    TwoPointFourGhz,
+    /// 5 GHz, channels 32 to 177
+    FiveGhz,
+    /// 6 GHz, channels 1 to 233
+    SixGhz,
+}
+
+impl Band {
+    /// Guesses the band from a channel number.
+    ///
+    /// 6 GHz reuses the channel numbers of the other bands, so a channel number alone
+    /// is never reported as `SixGhz`.
+    pub(crate) fn from_channel_number(number: u32) -> Option<Band> {
+        match number {
+            1..=14 => Some(Band::TwoPointFourGhz),
+            32..=177 => Some(Band::FiveGhz),
+            _ => None,
+        }
+    }
+
+    /// Returns the band a frequency in MHz belongs to.
+    pub(crate) fn from_frequency(mhz: u32) -> Option<Band> {
+        match mhz {
+            2412..=2484 => Some(Band::TwoPointFourGhz),
+            5160..=5885 => Some(Band::FiveGhz),
+            5955..=7115 => Some(Band::SixGhz),
+            _ => None,
+        }
+    }
+
+    /// Returns the centre frequencies in MHz of the band's 20 MHz channels, leaving out
+    /// the 5 GHz channels above 165 that few drivers know about.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub(crate) fn frequencies(self) -> Vec<u32> {
+        match self {
+            Band::TwoPointFourGhz => (2412..=2472).step_by(5).chain(Some(2484)).collect(),
+            Band::FiveGhz => (36..=64)
+                .step_by(4)
+                .chain((100..=144).step_by(4))
+                .chain((149..=165).step_by(4))
+                .map(|channel| 5000 + 5 * channel)
+                .collect(),
+            Band::SixGhz => (5955..=7115).step_by(20).collect(),
+        }
+    }
+}
+
+/// Returns the channel number for a frequency in MHz, or `None` if it isn't the
+/// centre of a 2.4, 5 or 6 GHz WiFi channel.
+pub(crate) fn channel_from_frequency(mhz: u32) -> Option<u32> {
+    match mhz {
+        2484 => Some(14),
+        2412..=2472 if mhz % 5 == 2 => Some((mhz - 2407) / 5),
+        5160..=5885 if mhz.is_multiple_of(5) => Some((mhz - 5000) / 5),
+        5955..=7115 if mhz.is_multiple_of(5) => Some((mhz - 5950) / 5),
+        _ => None,
+    }
+}
+
+/// Returns the centre frequency in MHz of a channel on `band`, the inverse of
+/// `channel_from_frequency`.
+pub(crate) fn frequency_from_channel(band: Band, number: u32) -> Option<u32> {
+    let mhz = match (band, number) {
+        (Band::TwoPointFourGhz, 14) => 2484,
+        (Band::TwoPointFourGhz, 1..=13) => 2407 + 5 * number,
+        (Band::FiveGhz, _) => 5000 + 5 * number,
+        (Band::SixGhz, _) => 5950 + 5 * number,
+        _ => return None,
+    };
+    channel_from_frequency(mhz).map(|_| mhz)
+}
+
+/// Channel a hotspot broadcasts on, see `Wifi::channel_info`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Channel {
+    /// primary channel number
+    pub number: u32,
+    pub band: Band,
+    /// channel width in MHz, if the platform reports it
+    pub width_mhz: Option<u16>,
+}
+
+impl Channel {
+    /// Parses a channel as found in `Wifi::channel`, e.g. `6` or `airport`'s `36,+1`
+    /// (40 MHz) and `149,80` (80 MHz).
+    pub(crate) fn parse(channel: &str) -> Option<Channel> {
+        let mut parts = channel.trim().splitn(2, ',');
+        let number: u32 = parts.next()?.trim().parse().ok()?;
+        let band = Band::from_channel_number(number)?;
+        let width_mhz = match parts.next().map(str::trim) {
+            Some("+1") | Some("-1") => Some(40),
+            Some(width) => width.parse().ok(),
+            None => None,
+        };
+
+        Some(Channel {
+            number,
+            band,
+            width_mhz,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_channel() {
+        assert_eq!(
+            Some(Channel {
+                number: 6,
+                band: Band::TwoPointFourGhz,
+                width_mhz: None,
+            }),
+            Channel::parse("6")
+        );
+        assert_eq!(
+            Some(Channel {
+                number: 36,
+                band: Band::FiveGhz,
+                width_mhz: Some(40),
+            }),
+            Channel::parse("36,+1")
+        );
+        assert_eq!(
+            Some(Channel {
+                number: 149,
+                band: Band::FiveGhz,
+                width_mhz: Some(80),
+            }),
+            Channel::parse("149,80")
+        );
+    }
+
+    #[test]
+    fn should_convert_frequency_to_channel() {
+        assert_eq!(Some(1), channel_from_frequency(2412));
+        assert_eq!(Some(14), channel_from_frequency(2484));
+        assert_eq!(Some(36), channel_from_frequency(5180));
+        assert_eq!(Some(165), channel_from_frequency(5825));
+        assert_eq!(Some(1), channel_from_frequency(5955));
+        assert_eq!(None, channel_from_frequency(900));
+        assert_eq!(None, channel_from_frequency(2413));
+    }
+
+    #[test]
+    fn should_convert_channel_to_frequency() {
+        assert_eq!(Some(2412), frequency_from_channel(Band::TwoPointFourGhz, 1));
+        assert_eq!(
+            Some(2484),
+            frequency_from_channel(Band::TwoPointFourGhz, 14)
+        );
+        assert_eq!(Some(5180), frequency_from_channel(Band::FiveGhz, 36));
+        assert_eq!(Some(6135), frequency_from_channel(Band::SixGhz, 37));
+        assert_eq!(None, frequency_from_channel(Band::TwoPointFourGhz, 36));
+        assert_eq!(None, frequency_from_channel(Band::FiveGhz, 1));
+    }
+
+    #[test]
+    fn should_list_band_frequencies() {
+        let frequencies = Band::TwoPointFourGhz.frequencies();
+        assert_eq!(14, frequencies.len());
+        assert_eq!(Some(&2484), frequencies.last());
+
+        let frequencies = Band::FiveGhz.frequencies();
+        assert_eq!(25, frequencies.len());
+        assert_eq!(vec![5180, 5200], frequencies[..2]);
+        assert!(frequencies.contains(&5745));
+        assert_eq!(Some(&5825), frequencies.last());
+
+        let frequencies = Band::SixGhz.frequencies();
+        assert_eq!(59, frequencies.len());
+        assert_eq!(Some(&7115), frequencies.last());
+    }
+
+    #[test]
+    fn should_not_parse_invalid_channel() {
+        assert_eq!(None, Channel::parse(""));
+        assert_eq!(None, Channel::parse("abc"));
+        assert_eq!(None, Channel::parse("20"));
+    }
+}
@@ -0,0 +1,98 @@
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::{Result, Wifi};
+
+/// A scan running in the background, see `start_scan`.
+///
+/// Dropping the handle without calling `results` cancels the scan.
+#[derive(Debug)]
+pub struct ScanHandle {
+    cancel: Arc<AtomicBool>,
+    thread: Option<JoinHandle<Result<Vec<Wifi>>>>,
+}
+
+impl ScanHandle {
+    /// Runs `scan` on a new thread, passing it the flag set by `cancel`.
+    pub(crate) fn spawn<F>(scan: F) -> ScanHandle
+    where
+        F: FnOnce(Arc<AtomicBool>) -> Result<Vec<Wifi>> + Send + 'static,
+    {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = cancel.clone();
+        ScanHandle {
+            cancel,
+            thread: Some(thread::spawn(move || scan(flag))),
+        }
+    }
+
+    /// Returns true once the scan has finished, i.e. `results` won't block.
+    pub fn is_finished(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_none_or(|thread| thread.is_finished())
+    }
+
+    /// Cancels the scan, `results` then returns `Error::Cancelled`. See
+    /// `scan_cancellable` for the backends that can't be interrupted.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns the hotspots found, blocking until the scan has finished.
+    pub fn results(mut self) -> Result<Vec<Wifi>> {
+        let thread = self.thread.take().expect("results are only taken once");
+        match thread.join() {
+            Ok(result) => result,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+impl Drop for ScanHandle {
+    fn drop(&mut self) {
+        // nobody is waiting for the results any more
+        if self.thread.is_some() {
+            self.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use std::time::Duration;
+
+    fn wait_for_cancel(cancel: Arc<AtomicBool>) -> Result<Vec<Wifi>> {
+        while !cancel.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        Err(Error::Cancelled)
+    }
+
+    #[test]
+    fn should_return_results_later() {
+        let handle = ScanHandle::spawn(|_| Ok(vec![Wifi::default()]));
+        assert_eq!(Ok(vec![Wifi::default()]), handle.results());
+    }
+
+    #[test]
+    fn should_cancel_scan() {
+        let handle = ScanHandle::spawn(wait_for_cancel);
+        assert!(!handle.is_finished());
+        handle.cancel();
+        assert_eq!(Err(Error::Cancelled), handle.results());
+    }
+
+    #[test]
+    fn should_cancel_scan_on_drop() {
+        let cancel = {
+            let handle = ScanHandle::spawn(wait_for_cancel);
+            handle.cancel.clone()
+        };
+        assert!(cancel.load(Ordering::Relaxed));
+    }
+}
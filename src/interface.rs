@@ -0,0 +1,14 @@
+/// A WiFi adapter of this machine, see `show_interfaces`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Interface {
+    /// e.g. `wlp2s0` on Linux, `en0` on macOS and `Wi-Fi` on Windows
+    pub name: String,
+    /// whether the interface is connected to a hotspot
+    pub connected: bool,
+    /// name of the hotspot the interface is connected to
+    pub ssid: Option<String>,
+    /// mac address of the hotspot the interface is connected to, not reported on Linux
+    pub bssid: Option<String>,
+    /// 802.11 standard of the connection, e.g. `802.11ac`, only reported on Windows
+    pub radio_type: Option<String>,
+}
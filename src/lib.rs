@@ -35,18 +35,106 @@
 //!
 //! Alternatively if you've cloned the the Git repo, you can run the above example
 //! using: `cargo run --example scan`.
+//!
+//! # Features
+//!
+//! - `android` - adds `scan_android`, listing the hotspots an Android device connected
+//!   with `adb` last saw, e.g. to survey from several phones at once. Needs `adb` from
+//!   the Android SDK platform tools on the `PATH`.
+//! - `binary` - adds `write_binary` and `read_binary`, a compact binary encoding of
+//!   scans for logs appending thousands of them.
+//! - `dbus` - on Linux, scan using NetworkManager's D-Bus API rather than parsing
+//!   `iw`'s output, falling back to `iw` when NetworkManager isn't running.
+//! - `netlink` - on Linux, scan by talking nl80211 over a netlink socket rather than
+//!   running `iw`. Triggering a scan needs `CAP_NET_ADMIN` (e.g. running as root),
+//!   without it the results of the kernel's last scan are returned. Falls back to `iw`
+//!   when nl80211 isn't available.
+//! - `oui` - adds `Wifi::vendor` and `group_by_vendor`, looking up the manufacturer of
+//!   a hotspot from its mac address.
+//! - `prometheus` - adds `to_prometheus`, formatting the hotspots' signals as Prometheus
+//!   metrics for monitoring agents to expose.
+//! - `tokio` - adds `scan_stream_async`, a `Stream` of scans for tokio users.
 
 //TODO need to find a way to move these out of lib and into sys or better still windows module
-#[cfg(target_os = "windows")]
-#[macro_use]
-extern crate itertools;
+#[cfg(feature = "tokio")]
+extern crate futures_core;
+#[cfg(all(target_os = "linux", feature = "netlink"))]
+extern crate libc;
 #[cfg(target_os = "windows")]
 extern crate regex;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(all(target_os = "linux", feature = "dbus"))]
+extern crate zbus;
 
+mod analysis;
+#[cfg(feature = "android")]
+mod android;
+mod backend;
+#[cfg(feature = "binary")]
+mod binary;
+mod channel;
+mod handle;
+mod interface;
+mod link;
+mod list;
+mod logger;
+mod options;
+mod output;
+#[cfg(feature = "prometheus")]
+mod prometheus;
+mod radio;
+mod security;
+mod signal;
+#[cfg(feature = "tokio")]
+mod stream;
 mod sys;
+mod table;
+mod throttle;
+#[cfg(feature = "oui")]
+mod vendor;
 
+pub use analysis::{
+    band_steered_ssids, best_bssid_for, by_channel, channel_load_estimate, channel_overlap,
+    color_conflicts, connectable, filter_by_band, filter_by_security, group_physical_aps,
+    open_clones_of_secured, same_channel_duplicate_ssids, scan_fingerprint, ssids,
+};
+#[cfg(feature = "android")]
+pub use android::scan_android;
+pub use backend::{BackendStatus, Tool};
+#[cfg(feature = "binary")]
+pub use binary::{read_binary, write_binary};
+pub use channel::{Band, Channel};
+pub use handle::ScanHandle;
+pub use interface::Interface;
+pub use link::LinkInfo;
+pub use list::WifiList;
+pub use logger::ScanLogger;
+pub use options::{FieldSet, ScanOptions, SortKey};
+pub use output::{scan_to_writer, OutputFormat};
+#[cfg(feature = "prometheus")]
+pub use prometheus::to_prometheus;
+pub use radio::RadioStatus;
+pub use security::{
+    canonical_security, detect_rogue, detect_rogue_with, suspicious_homoglyph_ssids,
+    suspicious_ssids, RogueThresholds, Security,
+};
+pub use signal::{SignalBounds, SignalLabel, SignalThresholds};
+#[cfg(feature = "tokio")]
+pub use stream::scan_stream_async;
+pub use table::format_table;
+#[cfg(feature = "oui")]
+pub use vendor::group_by_vendor;
+
+use std::collections::HashMap;
 use std::fmt;
+use std::io;
+use std::path::Path;
 use std::process::ExitStatus;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 type Result<T> = std::result::Result<T, Error>;
 
@@ -60,10 +148,22 @@ pub enum Error {
     FailedToParse,
     NoValue,
     HeaderNotFound(&'static str),
+    NeedsRoot(&'static str),
+    Parse { line: String, reason: String },
+    InvalidFrequency(u32),
+    InvalidRegDomain(String),
+    Cancelled,
+    Io(io::ErrorKind, String),
+    NoWirelessInterface,
+    RadioBlocked(RadioStatus),
+    InvalidUtf8,
+    Timeout,
 }
 
 /// Wifi struct used to return information about wifi hotspots
-#[derive(Debug, PartialEq, Eq, Default, Clone)]
+///
+/// Its `Debug` output leaves out empty fields, use `Wifi::full_debug` to see every field.
+#[derive(PartialEq, Eq, Default, Clone)]
 pub struct Wifi {
     /// mac address
     pub mac: String,
@@ -72,8 +172,608 @@ pub struct Wifi {
     pub channel: String,
     /// wifi signal strength in dBm
     pub signal_level: String,
-    /// this field is currently empty in the Linux version of the lib
+    /// security as normalized by `canonical_security`, e.g. `WPA2-Personal`
     pub security: String,
+    /// 802.11 standard, e.g. `802.11ac`, currently only reported on Windows
+    pub radio_type: Option<String>,
+    /// frequency of the primary channel in MHz, reported on Linux and on Windows when
+    /// `netsh` lists the band
+    pub frequency: Option<u32>,
+    /// signal to noise ratio in dB, only known when the platform reports the noise
+    /// level, which is rare outside of the connected hotspot
+    pub snr_db: Option<i32>,
+    /// how long ago the driver last heard the hotspot's beacon, currently only reported
+    /// on Linux. `iw` includes cached results, so large values mean a stale entry
+    pub last_seen_ms_ago: Option<u64>,
+    /// 802.11 capability flags, e.g. `ESS` and `Privacy`, currently only reported by `iw`
+    pub capabilities: Vec<String>,
+    /// hotspot name as broadcast, which isn't always valid UTF-8 unlike `ssid`. On macOS
+    /// and Windows the names are only reported as text, so these are `ssid`'s bytes
+    pub ssid_raw: Vec<u8>,
+    /// authentication and key management (AKM) suites of the RSN element, e.g. `PSK`
+    /// and `SAE`, named as `iw` names them. Currently only reported on Linux
+    pub akm_suites: Vec<String>,
+    /// most spatial streams the hotspot supports, from the MCS sets it advertises.
+    /// Currently only reported by `iw`
+    pub spatial_streams: Option<u8>,
+    /// position of the secondary channel of a 40 MHz channel relative to the primary
+    /// one: `1` above, `-1` below and `0` for a 20 MHz channel. Currently only reported
+    /// by `iw`
+    pub secondary_channel_offset: Option<i8>,
+    /// 802.11ax (WiFi 6) BSS color, `0` to `63`, telling the hotspot's frames apart from
+    /// those of neighbours on the same channel. `None` when the hotspot doesn't
+    /// advertise one or has disabled it. Currently only reported by `iw`
+    pub bss_color: Option<u8>,
+    /// whether the hotspot advertises WMM, the QoS prioritising voice and video traffic
+    /// that most hotspots since 802.11n support. Currently only reported by `iw`
+    pub qos_enabled: bool,
+    /// number of clients associated with the hotspot, from its BSS load element.
+    /// Currently only reported by `iw`
+    pub station_count: Option<u16>,
+    /// share of the time the hotspot sensed the channel busy, from `0` (idle) to `255`
+    /// (always busy), from its BSS load element. Currently only reported by `iw`
+    pub channel_utilization: Option<u8>,
+    /// vendor specific information elements as lowercase hex, starting with the
+    /// vendor's OUI, e.g. `0050f204...` for WPS. Only reported by `iw` when
+    /// `ScanOptions::vendor_ies` is set
+    pub vendor_ies: Vec<String>,
+    /// cipher protecting the hotspot's traffic, e.g. `CCMP` (AES) or `TKIP`, telling
+    /// apart hotspots `security` reports alike. Currently only reported on Windows,
+    /// where open networks report `None`
+    pub encryption: Option<String>,
+    /// highest bit rate the hotspot supports in Mbit/s. Currently only reported by
+    /// `nmcli` on Linux
+    pub max_rate_mbps: Option<u32>,
+    /// whether this machine is connected to the hotspot. Currently only reported by
+    /// `nmcli` on Linux, use `connected_network` on other platforms
+    pub connected: bool,
+}
+
+/// Hotspots found by `scan_result`, along with whether they come from a scan that just
+/// happened or from results cached by the platform.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ScanResult {
+    pub wifis: Vec<Wifi>,
+    /// true if the radio scanned for these hotspots, false if they are what the
+    /// platform last saw, which may lag behind your movements
+    pub was_fresh: bool,
+}
+
+impl ScanResult {
+    #[cfg_attr(not(any(target_os = "linux", target_os = "macos")), allow(dead_code))]
+    pub(crate) fn fresh(wifis: Vec<Wifi>) -> ScanResult {
+        ScanResult {
+            wifis,
+            was_fresh: true,
+        }
+    }
+
+    #[cfg_attr(not(any(target_os = "linux", target_os = "windows")), allow(dead_code))]
+    pub(crate) fn cached(wifis: Vec<Wifi>) -> ScanResult {
+        ScanResult {
+            wifis,
+            was_fresh: false,
+        }
+    }
+}
+
+/// Hotspots found by `scan_report`, along with anything odd about the scan that didn't
+/// make it fail.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ScanReport {
+    pub wifis: Vec<Wifi>,
+    /// same as `ScanResult::was_fresh`
+    pub was_fresh: bool,
+    pub warnings: Vec<ScanWarning>,
+}
+
+impl ScanReport {
+    /// Reports `result`, warning if the scanning tool's `output` has lines for which
+    /// `is_hotspot_line` is true but no hotspots were parsed from them.
+    pub(crate) fn checked(
+        result: ScanResult,
+        output: &str,
+        is_hotspot_line: fn(&str) -> bool,
+    ) -> ScanReport {
+        let mut warnings = Vec::new();
+        if result.wifis.is_empty() {
+            let lines = output.lines().filter(|line| is_hotspot_line(line)).count();
+            if lines > 0 {
+                warnings.push(ScanWarning::UnparsedOutput { lines });
+            }
+        }
+        ScanReport {
+            wifis: result.wifis,
+            was_fresh: result.was_fresh,
+            warnings,
+        }
+    }
+
+    pub(crate) fn into_result(self) -> ScanResult {
+        ScanResult {
+            wifis: self.wifis,
+            was_fresh: self.was_fresh,
+        }
+    }
+}
+
+impl From<ScanResult> for ScanReport {
+    fn from(result: ScanResult) -> ScanReport {
+        ScanReport {
+            wifis: result.wifis,
+            was_fresh: result.was_fresh,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+/// Something odd about a scan that didn't make it fail, see `scan_report`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanWarning {
+    /// the scanning tool succeeded and printed `lines` lines that look like hotspots,
+    /// but none could be parsed, e.g. because a new version changed its output
+    UnparsedOutput { lines: usize },
+    /// `lines` lines of the scanning tool's output couldn't be parsed and were skipped,
+    /// the hotspots of the other lines are returned
+    SkippedLines { lines: usize },
+}
+
+impl fmt::Display for ScanWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScanWarning::UnparsedOutput { lines } => write!(
+                f,
+                "Couldn't parse any hotspots from {} lines of output",
+                lines
+            ),
+            ScanWarning::SkippedLines { lines } => {
+                write!(
+                    f,
+                    "Skipped {} lines of output that couldn't be parsed",
+                    lines
+                )
+            }
+        }
+    }
+}
+
+impl Wifi {
+    /// Path loss exponent used by `estimated_distance_m`, typical of an office or home
+    /// with a few walls. Free space is `2.0`, dense buildings go up to about `4.0`.
+    pub const DEFAULT_PATH_LOSS_EXPONENT: f64 = 3.0;
+
+    /// Returns the `channel` as a typed `Channel`, or `None` if it couldn't be parsed.
+    ///
+    /// The band is derived from the `frequency` when reported, otherwise from the
+    /// channel number, which can't tell 6 GHz channels apart. The width is only known
+    /// when the platform reports it (currently `airport` on macOS).
+    pub fn channel_info(&self) -> Option<Channel> {
+        let mut channel = Channel::parse(&self.channel)?;
+        if let Some(band) = self.frequency.and_then(Band::from_frequency) {
+            channel.band = band;
+        }
+        Some(channel)
+    }
+
+    /// Returns true if the hotspot's primary channel is a 5 GHz DFS channel (52 to 144),
+    /// on which it has to watch for radar and move away when it detects any. Hotspots on
+    /// these channels may disappear for a while, which can explain an intermittent
+    /// network.
+    ///
+    /// Only the primary channel is checked, so e.g. a 160 MHz channel starting at 36,
+    /// whose upper half is on DFS channels, isn't reported.
+    pub fn is_dfs_channel(&self) -> bool {
+        self.channel_info().is_some_and(|channel| {
+            channel.band == Band::FiveGhz && (52..=144).contains(&channel.number)
+        })
+    }
+
+    /// Returns the `signal_level` in dBm, or `None` if it couldn't be parsed.
+    /// `iw` reports fractional values (e.g. `-67.00`), these are rounded.
+    pub fn signal_dbm(&self) -> Option<i32> {
+        self.signal_level
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .map(|dbm| dbm.round() as i32)
+    }
+
+    /// Returns the signal in milliwatts (`10^(dBm / 10)`), for tools working in linear
+    /// power units, or `None` if the signal couldn't be parsed. Unlike `signal_dbm`,
+    /// fractional dBm values aren't rounded.
+    pub fn signal_milliwatts(&self) -> Option<f64> {
+        let dbm = self.signal_level.trim().parse::<f64>().ok()?;
+        Some(10f64.powf(dbm / 10.0))
+    }
+
+    /// Returns the signal as a quality percentage using the default `SignalBounds`, or
+    /// `None` if the signal couldn't be parsed.
+    pub fn quality_percent(&self) -> Option<u8> {
+        self.signal_dbm()
+            .map(|dbm| SignalBounds::default().to_percent(dbm))
+    }
+
+    /// Labels the signal for display, e.g. `Good`, using the default
+    /// `SignalThresholds`. Returns `SignalLabel::Unknown` if the signal couldn't be
+    /// parsed.
+    pub fn signal_label(&self) -> SignalLabel {
+        self.signal_label_with(SignalThresholds::default())
+    }
+
+    /// Same as `signal_label`, with your own thresholds.
+    pub fn signal_label_with(&self, thresholds: SignalThresholds) -> SignalLabel {
+        self.signal_dbm()
+            .map_or(SignalLabel::Unknown, |dbm| thresholds.label(dbm))
+    }
+
+    /// Estimates the distance to the hotspot in metres from its signal, using the
+    /// log-distance path loss model with `DEFAULT_PATH_LOSS_EXPONENT`.
+    ///
+    /// `tx_power_dbm` is the reference signal measured 1 metre from the hotspot, around
+    /// `-40` dBm for most access points. This is a rough approximation only, walls,
+    /// antennas and interference easily put it off by a factor of two or more, so use
+    /// it to rank hotspots rather than to locate them. Returns `None` when the signal
+    /// couldn't be parsed.
+    pub fn estimated_distance_m(&self, tx_power_dbm: i32) -> Option<f64> {
+        self.estimated_distance_m_with_exponent(tx_power_dbm, Wifi::DEFAULT_PATH_LOSS_EXPONENT)
+    }
+
+    /// Same as `estimated_distance_m`, with the environment's path loss exponent given
+    /// explicitly. Returns `None` if the exponent isn't positive.
+    pub fn estimated_distance_m_with_exponent(
+        &self,
+        tx_power_dbm: i32,
+        path_loss_exponent: f64,
+    ) -> Option<f64> {
+        if path_loss_exponent <= 0.0 {
+            return None;
+        }
+        let path_loss = f64::from(tx_power_dbm - self.signal_dbm()?);
+        Some(10f64.powf(path_loss / (10.0 * path_loss_exponent)))
+    }
+
+    /// Returns true if the hotspot doesn't broadcast its SSID, i.e. the SSID is empty
+    /// or made up of null bytes (which `iw` prints as `\x00`).
+    pub fn is_hidden(&self) -> bool {
+        self.ssid.replace("\\x00", "").trim_matches('\0').is_empty()
+    }
+
+    /// Returns true if the hotspot broadcasts on `frequency` (in MHz). When the
+    /// platform doesn't report a `frequency` the channel number is compared instead.
+    fn is_on_frequency(&self, frequency: u32) -> bool {
+        match self.frequency {
+            Some(own_frequency) => own_frequency == frequency,
+            None => self.channel_info().is_some_and(|channel| {
+                Some(channel.band) == Band::from_frequency(frequency)
+                    && Some(channel.number) == channel::channel_from_frequency(frequency)
+            }),
+        }
+    }
+
+    /// Returns true if the hotspot sets the Privacy capability, i.e. requires encryption.
+    /// Unlike `security` this catches WEP networks, which don't advertise any
+    /// authentication suites. Always false when `capabilities` isn't reported.
+    pub fn has_privacy(&self) -> bool {
+        self.capabilities
+            .iter()
+            .any(|capability| capability == "Privacy")
+    }
+
+    /// Returns the length of the SSID in bytes as broadcast, i.e. of `ssid_raw` when
+    /// reported.
+    pub fn ssid_byte_len(&self) -> usize {
+        if self.ssid_raw.is_empty() {
+            self.ssid.len()
+        } else {
+            self.ssid_raw.len()
+        }
+    }
+
+    /// Returns false if the SSID is longer than the 32 bytes 802.11 allows, which only
+    /// malformed or malicious beacons send.
+    pub fn has_valid_ssid_length(&self) -> bool {
+        self.ssid_byte_len() <= 32
+    }
+
+    /// Returns true if the hotspot is in WPA3 transition mode, i.e. accepts both WPA2
+    /// (PSK) and WPA3 (SAE) clients, so older devices can still connect while `security`
+    /// reports `WPA3-Personal`.
+    ///
+    /// Only works on Linux as that's the only platform reporting `akm_suites`, it's
+    /// always false on macOS and Windows.
+    pub fn is_wpa3_transition(&self) -> bool {
+        let has_suite = |suites: &[&str]| {
+            self.akm_suites
+                .iter()
+                .any(|suite| suites.contains(&suite.as_str()))
+        };
+        has_suite(&["PSK", "FT/PSK", "PSK/SHA-256"]) && has_suite(&["SAE", "FT/SAE"])
+    }
+
+    /// Returns true if the hotspot supports fast roaming (802.11r Fast Transition), i.e.
+    /// advertises an `FT/` authentication suite such as `FT/PSK` or `FT/IEEE 802.1X`.
+    ///
+    /// Only works on Linux as that's the only platform reporting `akm_suites`, it's
+    /// always false on macOS and Windows.
+    pub fn supports_fast_roaming(&self) -> bool {
+        self.akm_suites.iter().any(|suite| suite.starts_with("FT/"))
+    }
+
+    /// Returns true if joining the hotspot needs a username and password (802.1X)
+    /// rather than just a key, from either its `security` (e.g. `WPA2-Enterprise`) or
+    /// an 802.1X authentication suite in `akm_suites`.
+    pub fn is_enterprise(&self) -> bool {
+        self.security_type() == Security::Enterprise
+            || self.akm_suites.iter().any(|suite| suite.contains("802.1X"))
+    }
+
+    /// Returns a copy of the hotspot with the last three octets of its mac address
+    /// masked, e.g. `00:35:1a:xx:xx:xx`, so scans can be shared (e.g. in bug reports)
+    /// without the BSSIDs that locate the hotspots. The vendor prefix (OUI) is kept,
+    /// use `anonymized_with` to mask it too.
+    ///
+    /// Only the mac address is changed. Masked hotspots of the same vendor can't be
+    /// told apart any more, hashing the octets instead wouldn't hide them as there are
+    /// only 2^24 of them to try.
+    pub fn anonymized(&self) -> Wifi {
+        self.anonymized_with(true)
+    }
+
+    /// Same as `anonymized`, masking the whole mac address unless `keep_oui` is set.
+    pub fn anonymized_with(&self, keep_oui: bool) -> Wifi {
+        let mut wifi = self.clone();
+        if !wifi.mac.is_empty() {
+            let kept = if keep_oui { 3 } else { 0 };
+            wifi.mac = normalize_mac(&self.mac)
+                .split(':')
+                .enumerate()
+                .map(|(index, octet)| if index < kept { octet } else { "xx" })
+                .collect::<Vec<_>>()
+                .join(":");
+        }
+        wifi
+    }
+
+    /// Returns the `security` normalized across platforms.
+    pub fn security_type(&self) -> Security {
+        Security::parse(&self.security)
+    }
+
+    /// Returns a value whose `Debug` output includes every field, even empty ones.
+    pub fn full_debug(&self) -> impl fmt::Debug + '_ {
+        FullDebug(self)
+    }
+
+    /// Returns a description of the hotspot with every populated field labelled on a
+    /// line of its own, e.g. for a detailed view of one hotspot next to `format_table`'s
+    /// overview. Fields that aren't reported are left out.
+    pub fn detail_string(&self) -> String {
+        let join = |values: &[String], separator| Some(values.join(separator));
+        let fields = vec![
+            ("MAC", Some(self.mac.clone())),
+            ("SSID", Some(self.ssid.clone())),
+            (
+                "SSID bytes",
+                // only interesting when the SSID isn't valid UTF-8
+                Some(&self.ssid_raw)
+                    .filter(|raw| !raw.is_empty() && raw.as_slice() != self.ssid.as_bytes())
+                    .map(|raw| raw.iter().map(|byte| format!("{:02x}", byte)).collect()),
+            ),
+            ("Channel", Some(self.channel.clone())),
+            (
+                "Band",
+                self.channel_info().map(|channel| {
+                    match channel.band {
+                        Band::TwoPointFourGhz => "2.4 GHz",
+                        Band::FiveGhz => "5 GHz",
+                        Band::SixGhz => "6 GHz",
+                    }
+                    .to_string()
+                }),
+            ),
+            (
+                "Frequency",
+                self.frequency.map(|mhz| format!("{} MHz", mhz)),
+            ),
+            (
+                "Signal",
+                Some(&self.signal_level)
+                    .filter(|signal| !signal.is_empty())
+                    .map(|signal| format!("{} dBm", signal)),
+            ),
+            ("SNR", self.snr_db.map(|snr| format!("{} dB", snr))),
+            ("Security", Some(self.security.clone())),
+            ("Encryption", self.encryption.clone()),
+            (
+                "Connected",
+                Some("yes".to_string()).filter(|_| self.connected),
+            ),
+            (
+                "Max rate",
+                self.max_rate_mbps.map(|rate| format!("{} Mbit/s", rate)),
+            ),
+            ("AKM suites", join(&self.akm_suites, ", ")),
+            ("Radio type", self.radio_type.clone()),
+            (
+                "Spatial streams",
+                self.spatial_streams.map(|streams| streams.to_string()),
+            ),
+            (
+                "Secondary channel",
+                self.secondary_channel_offset
+                    .map(|offset| format!("{:+}", offset)),
+            ),
+            ("BSS color", self.bss_color.map(|color| color.to_string())),
+            (
+                "QoS (WMM)",
+                Some("yes".to_string()).filter(|_| self.qos_enabled),
+            ),
+            (
+                "Stations",
+                self.station_count.map(|count| count.to_string()),
+            ),
+            (
+                "Channel utilization",
+                self.channel_utilization
+                    .map(|utilization| format!("{}%", u32::from(utilization) * 100 / 255)),
+            ),
+            ("Capabilities", join(&self.capabilities, " ")),
+            (
+                "Last seen",
+                self.last_seen_ms_ago.map(|ms| format!("{} ms ago", ms)),
+            ),
+            #[cfg(feature = "oui")]
+            ("Vendor", self.vendor().map(str::to_string)),
+            ("Vendor IEs", join(&self.vendor_ies, ", ")),
+        ];
+
+        let fields: Vec<_> = fields
+            .into_iter()
+            .filter_map(|(label, value)| Some((label, value.filter(|value| !value.is_empty())?)))
+            .collect();
+        let width = fields
+            .iter()
+            .map(|(label, _)| label.len() + 1)
+            .max()
+            .unwrap_or(0);
+        fields
+            .iter()
+            .map(|(label, value)| {
+                format!(
+                    "{:<width$} {}\n",
+                    format!("{}:", label),
+                    value,
+                    width = width
+                )
+            })
+            .collect()
+    }
+
+    /// Fills in the fields left empty from `other`, e.g. to combine what two tools
+    /// reported about the same hotspot.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub(crate) fn fill_missing(&mut self, other: Wifi) {
+        fn fill<T: PartialEq + Default>(value: &mut T, other: T) {
+            if *value == T::default() {
+                *value = other;
+            }
+        }
+
+        fill(&mut self.mac, other.mac);
+        fill(&mut self.ssid, other.ssid);
+        fill(&mut self.channel, other.channel);
+        fill(&mut self.signal_level, other.signal_level);
+        fill(&mut self.security, other.security);
+        fill(&mut self.radio_type, other.radio_type);
+        fill(&mut self.frequency, other.frequency);
+        fill(&mut self.snr_db, other.snr_db);
+        fill(&mut self.last_seen_ms_ago, other.last_seen_ms_ago);
+        fill(&mut self.capabilities, other.capabilities);
+        fill(&mut self.ssid_raw, other.ssid_raw);
+        fill(&mut self.akm_suites, other.akm_suites);
+        fill(&mut self.spatial_streams, other.spatial_streams);
+        fill(
+            &mut self.secondary_channel_offset,
+            other.secondary_channel_offset,
+        );
+        fill(&mut self.bss_color, other.bss_color);
+        fill(&mut self.qos_enabled, other.qos_enabled);
+        fill(&mut self.station_count, other.station_count);
+        fill(&mut self.channel_utilization, other.channel_utilization);
+        fill(&mut self.vendor_ies, other.vendor_ies);
+        fill(&mut self.encryption, other.encryption);
+        fill(&mut self.max_rate_mbps, other.max_rate_mbps);
+        fill(&mut self.connected, other.connected);
+    }
+
+    /// Fields shown by `Debug`, along with whether they're populated.
+    fn debug_fields(&self) -> Vec<(&'static str, &dyn fmt::Debug, bool)> {
+        vec![
+            ("mac", &self.mac, !self.mac.is_empty()),
+            ("ssid", &self.ssid, !self.ssid.is_empty()),
+            ("channel", &self.channel, !self.channel.is_empty()),
+            (
+                "signal_level",
+                &self.signal_level,
+                !self.signal_level.is_empty(),
+            ),
+            ("security", &self.security, !self.security.is_empty()),
+            ("radio_type", &self.radio_type, self.radio_type.is_some()),
+            ("frequency", &self.frequency, self.frequency.is_some()),
+            ("snr_db", &self.snr_db, self.snr_db.is_some()),
+            (
+                "last_seen_ms_ago",
+                &self.last_seen_ms_ago,
+                self.last_seen_ms_ago.is_some(),
+            ),
+            (
+                "capabilities",
+                &self.capabilities,
+                !self.capabilities.is_empty(),
+            ),
+            (
+                "ssid_raw",
+                &self.ssid_raw,
+                // only interesting when the SSID isn't valid UTF-8
+                !self.ssid_raw.is_empty() && self.ssid_raw != self.ssid.as_bytes(),
+            ),
+            ("akm_suites", &self.akm_suites, !self.akm_suites.is_empty()),
+            (
+                "spatial_streams",
+                &self.spatial_streams,
+                self.spatial_streams.is_some(),
+            ),
+            (
+                "secondary_channel_offset",
+                &self.secondary_channel_offset,
+                self.secondary_channel_offset.is_some(),
+            ),
+            ("bss_color", &self.bss_color, self.bss_color.is_some()),
+            ("qos_enabled", &self.qos_enabled, self.qos_enabled),
+            (
+                "station_count",
+                &self.station_count,
+                self.station_count.is_some(),
+            ),
+            (
+                "channel_utilization",
+                &self.channel_utilization,
+                self.channel_utilization.is_some(),
+            ),
+            ("vendor_ies", &self.vendor_ies, !self.vendor_ies.is_empty()),
+            ("encryption", &self.encryption, self.encryption.is_some()),
+            (
+                "max_rate_mbps",
+                &self.max_rate_mbps,
+                self.max_rate_mbps.is_some(),
+            ),
+            ("connected", &self.connected, self.connected),
+        ]
+    }
+}
+
+impl fmt::Debug for Wifi {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug = f.debug_struct("Wifi");
+        for (name, value, populated) in self.debug_fields() {
+            if populated {
+                debug.field(name, value);
+            }
+        }
+        debug.finish()
+    }
+}
+
+struct FullDebug<'a>(&'a Wifi);
+
+impl<'a> fmt::Debug for FullDebug<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug = f.debug_struct("Wifi");
+        for (name, value, _) in self.0.debug_fields() {
+            debug.field(name, value);
+        }
+        debug.finish()
+    }
 }
 
 impl fmt::Display for Error {
@@ -90,14 +790,746 @@ impl fmt::Display for Error {
             Error::HeaderNotFound(header) => {
                 write!(f, "Did not find header {} but expected it", header)
             }
+            Error::NeedsRoot(command) => {
+                write!(f, "{} needs to be run as root, e.g. with sudo", command)
+            }
+            Error::Parse { line, reason } => write!(f, "Failed to parse {:?}: {}", line, reason),
+            Error::InvalidFrequency(frequency) => {
+                write!(f, "{} MHz isn't a WiFi frequency", frequency)
+            }
+            Error::InvalidRegDomain(country) => {
+                write!(f, "{:?} isn't a regulatory domain country code", country)
+            }
+            Error::Cancelled => write!(f, "Scan was cancelled"),
+            Error::Io(_, reason) => write!(f, "IO error: {}", reason),
+            Error::NoWirelessInterface => write!(f, "No WiFi hardware found"),
+            Error::RadioBlocked(RadioStatus::HardBlocked) => {
+                write!(f, "WiFi is turned off by a hardware switch")
+            }
+            Error::RadioBlocked(_) => write!(f, "WiFi is turned off"),
+            Error::InvalidUtf8 => write!(f, "Command output isn't valid UTF-8"),
+            Error::Timeout => write!(f, "Timed out"),
         }
     }
 }
 
 impl std::error::Error for Error {}
 
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err.kind(), err.to_string())
+    }
+}
+
 /// Returns a list of WiFi hotspots in your area.
-/// Uses `airport` on macOS and `iw` on Linux.
+/// Uses `airport` on macOS, `iw` on Linux (falling back to `iwlist` when `iw` isn't
+/// installed) and `netsh` on Windows.
 pub fn scan() -> Result<Vec<Wifi>> {
-    crate::sys::scan()
+    scan_result(false).map(|result| result.wifis)
+}
+
+/// Same as `scan`, but returns the hotspots as an iterator, parsing them one at a time
+/// as it's advanced, e.g. to stop at the first match with `find` in a crowded area.
+///
+/// The scanning tool's whole output is still captured before the first hotspot is
+/// returned, so this only saves the memory and time of parsing hotspots you don't
+/// look at. Only `iw` on Linux is parsed lazily, the other backends and the `dbus` and
+/// `netlink` features parse everything up front, as `scan` does.
+pub fn scan_iter() -> Result<impl Iterator<Item = Wifi>> {
+    crate::sys::scan_iter(&ScanOptions::default())
+}
+
+/// Same as `scan`, but also reports anything odd about the scan, so an area without
+/// hotspots can be told apart from output that couldn't be parsed, which `scan` returns
+/// as an empty list too.
+///
+/// Only the output of `iw`, `wpa_cli` and `nmcli` on Linux, `airport` on macOS and
+/// `netsh` on Windows is checked. The `dbus` and `netlink` features and `iwlist` never
+/// warn.
+pub fn scan_report() -> Result<ScanReport> {
+    crate::sys::scan_report(&ScanOptions::default(), &AtomicBool::new(false))
+}
+
+/// Same as `scan`, but passes each hotspot through `f` as it's parsed, keeping those it
+/// returns, e.g. to drop hidden networks or fill in details from elsewhere in one pass.
+///
+/// `f` runs before any deduping or sorting: it sees every hotspot the platform reports,
+/// duplicates included, in the platform's order, and `WifiList::dedupe` or
+/// `WifiList::sorted_by_signal` afterwards work on what it returned. To run a closure
+/// after `ScanOptions::passes` dedupes and `ScanOptions::sort_by` sorts, `filter_map`
+/// the results of `scan_with_options` instead.
+pub fn scan_with<F: Fn(Wifi) -> Option<Wifi>>(f: F) -> Result<Vec<Wifi>> {
+    Ok(scan_iter()?.filter_map(f).collect())
+}
+
+/// Same as `scan`, but puts the hotspots in `buf` so its capacity is reused when
+/// polling in a loop, rather than returning a new `Vec` every scan.
+///
+/// `buf` is cleared first, so it's left empty when the scan fails. Parsing the
+/// platform's output still allocates, this only saves growing a new list each time.
+pub fn scan_into(buf: &mut Vec<Wifi>) -> Result<()> {
+    buf.clear();
+    buf.append(&mut scan()?);
+    Ok(())
+}
+
+/// Same as `scan`, but never fails: an empty list is returned instead, for daemons and
+/// other callers happy with best-effort results.
+///
+/// The error is discarded, so there's no telling a failed scan from an area without
+/// hotspots. Use `scan` when that matters or to handle errors yourself.
+pub fn try_scan() -> Vec<Wifi> {
+    scan().unwrap_or_default()
+}
+
+/// Same as `scan`, but gives up as soon as `cancel` is set from another thread, killing
+/// the scanning command and returning `Error::Cancelled`.
+///
+/// The flag is polled every 50ms while the command runs. The `dbus` and `netlink`
+/// backends can't be interrupted, so they run to completion before the flag is checked.
+pub fn scan_cancellable(cancel: Arc<AtomicBool>) -> Result<Vec<Wifi>> {
+    crate::sys::scan(&ScanOptions::default(), &cancel).map(|result| result.wifis)
+}
+
+/// Starts a scan in the background and returns straight away, so you can do other work
+/// while the radio scans. Call `ScanHandle::results` to get the hotspots once you need
+/// them, which blocks until the scan has finished.
+///
+/// Scans the same way as `scan`, on a thread of its own.
+pub fn start_scan() -> ScanHandle {
+    ScanHandle::spawn(scan_cancellable)
+}
+
+/// Scans every `interval` on a thread of its own, appending the hotspots found to the
+/// file at `path` in `format`, e.g. to collect a survey unattended. Call
+/// `ScanLogger::stop` to stop.
+///
+/// Once the file has grown to `ScanLogger::DEFAULT_MAX_BYTES` it's moved to `<path>.1`,
+/// replacing the previous one, and a new file is started. Failed scans and writes are
+/// printed to stderr and logging carries on with the next scan.
+pub fn log_scans_to_file<P: AsRef<Path>>(
+    path: P,
+    interval: Duration,
+    format: OutputFormat,
+) -> ScanLogger {
+    log_scans_to_file_with(path, interval, format, ScanLogger::DEFAULT_MAX_BYTES)
+}
+
+/// Same as `log_scans_to_file`, rotating the file once it has grown to `max_bytes`.
+pub fn log_scans_to_file_with<P: AsRef<Path>>(
+    path: P,
+    interval: Duration,
+    format: OutputFormat,
+    max_bytes: u64,
+) -> ScanLogger {
+    ScanLogger::spawn(
+        scan,
+        path.as_ref().to_path_buf(),
+        interval,
+        format,
+        max_bytes,
+    )
+}
+
+/// Returns the hotspots found by the last scan, without scanning again where the
+/// platform allows it. The results may be stale, but unlike `scan` this never
+/// interrupts the current connection, which some drivers drop while scanning.
+///
+/// Uses `iw dev <interface> scan dump` on Linux, and `netsh` on Windows which never
+/// scans itself. `airport` on macOS and `iwlist` on Linux can't return cached results,
+/// so these fall back to a fresh scan.
+pub fn scan_cached() -> Result<Vec<Wifi>> {
+    scan_result(true).map(|result| result.wifis)
+}
+
+/// Same as `scan`, or `scan_cached` when `cached` is set, also reporting whether the
+/// radio actually scanned or cached results were returned, e.g. to mark stale results.
+///
+/// Results are only fresh when a scan was triggered: `netsh` on Windows and
+/// NetworkManager (the `dbus` feature) always list cached results, and the `netlink`
+/// feature returns cached results when it isn't allowed to scan.
+pub fn scan_result(cached: bool) -> Result<ScanResult> {
+    let options = ScanOptions::default();
+    if cached {
+        crate::sys::scan_cached(&options)
+    } else {
+        crate::sys::scan(&options, &AtomicBool::new(false))
+    }
+}
+
+/// Returns a list of WiFi hotspots in your area, filtered according to `options`.
+pub fn scan_with_options(options: &ScanOptions) -> Result<Vec<Wifi>> {
+    if options.require_wifi_hardware && !has_wifi_hardware() {
+        return Err(Error::NoWirelessInterface);
+    }
+    #[cfg(target_os = "linux")]
+    {
+        if options.require_radio_unblocked {
+            // without `rfkill` the scan itself has to tell
+            match radio_status() {
+                Ok(status) if status.is_blocked() => return Err(Error::RadioBlocked(status)),
+                _ => {}
+            }
+        }
+    }
+    if let Some(&frequency) = options
+        .frequencies
+        .iter()
+        .find(|&&frequency| channel::channel_from_frequency(frequency).is_none())
+    {
+        return Err(Error::InvalidFrequency(frequency));
+    }
+    if let Some(country) = options.reg_domain.as_ref().filter(|country| {
+        *country != "00"
+            && !(country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()))
+    }) {
+        return Err(Error::InvalidRegDomain(country.clone()));
+    }
+
+    let mut wifis = throttle::THROTTLE.scan(options, || {
+        crate::sys::with_reg_domain(options, || {
+            let mut passes = Vec::new();
+            for _ in 0..options.passes.max(1) {
+                passes.push(crate::sys::scan(options, &AtomicBool::new(false))?.wifis);
+            }
+            Ok(merge_passes(passes))
+        })
+    })?;
+
+    if !options.frequencies.is_empty() {
+        wifis.retain(|wifi| {
+            options
+                .frequencies
+                .iter()
+                .any(|&frequency| wifi.is_on_frequency(frequency))
+        });
+    }
+
+    if let Some(band) = options.band {
+        wifis.retain(|wifi| {
+            wifi.channel_info()
+                .is_some_and(|channel| channel.band == band)
+        });
+    }
+
+    if options.exclude_connected {
+        // e.g. `iw` isn't installed or `wdutil` needs root, the scan itself succeeded
+        if let Some(connected) = connected_network().ok().flatten() {
+            let connected_mac = normalize_mac(&connected.mac);
+            wifis.retain(|wifi| normalize_mac(&wifi.mac) != connected_mac);
+        }
+    }
+
+    if let Some(key) = options.sort_by {
+        key.sort(&mut wifis);
+    }
+
+    if let Some(limit) = options.limit {
+        wifis.truncate(limit);
+    }
+
+    Ok(wifis)
+}
+
+/// Merges the results of several scans, keeping the strongest signal of each hotspot
+/// found more than once, see `ScanOptions::passes`.
+fn merge_passes(passes: Vec<Vec<Wifi>>) -> Vec<Wifi> {
+    if passes.len() == 1 {
+        return passes.into_iter().flatten().collect();
+    }
+    WifiList(passes.into_iter().flatten().collect())
+        .dedupe()
+        .into_vec()
+}
+
+/// Same as `scan`, returning a `WifiList` to query the hotspots with.
+pub fn scan_list() -> Result<WifiList> {
+    scan().map(WifiList)
+}
+
+/// Same as `scan`, returning the hotspots keyed by their normalized mac address, e.g. to
+/// look them up when tracking or diffing scans. A hotspot reported more than once, e.g.
+/// by several interfaces, is kept once with its strongest signal, see
+/// `WifiList::into_map`.
+pub fn scan_map() -> Result<HashMap<String, Wifi>> {
+    scan_list().map(WifiList::into_map)
+}
+
+/// Returns true if the machine has a WiFi interface that can scan, so you can tell the
+/// user "no WiFi on this device" instead of showing an empty or confusing scan result.
+/// Uses `iw dev` on Linux (or `/sys/class/net` when `iw` isn't installed),
+/// `networksetup -listallhardwareports` on macOS and `netsh wlan show interfaces` on
+/// Windows.
+///
+/// See also `ScanOptions::require_wifi_hardware`.
+pub fn has_wifi_hardware() -> bool {
+    crate::sys::has_wifi_hardware()
+}
+
+/// Checks which of the tools used to scan are installed, so you can tell the user
+/// what's missing before scanning. Probes `iw`, `iwlist`, `wpa_cli` and `nmcli` on Linux,
+/// `airport` on macOS and `netsh` on Windows.
+///
+/// `BackendStatus::preferred` is the tool `scan` will use. On Linux the `dbus` and
+/// `netlink` features are tried before any of these tools.
+pub fn check_backend() -> Result<BackendStatus> {
+    crate::sys::check_backend()
+}
+
+/// Scans and returns the hotspot with the strongest signal, or `None` if no hotspots
+/// were found. Hotspots whose signal couldn't be parsed are only returned if no other
+/// hotspots were found.
+pub fn strongest() -> Result<Option<Wifi>> {
+    Ok(scan()?.into_iter().max_by_key(Wifi::signal_dbm))
+}
+
+/// Scans `samples` times, `interval` apart, and returns the signal in dBm of the hotspot
+/// with mac address `bssid` in each scan, e.g. to walk towards a device. A sample is
+/// `None` when the hotspot wasn't found (or its signal couldn't be parsed).
+///
+/// The mac address is matched ignoring case and `airport`'s missing leading zeros. A
+/// failed scan stops the tracking and returns its error. Each scan takes a few seconds
+/// on most platforms, on top of `interval`.
+pub fn track_bssid(bssid: &str, samples: usize, interval: Duration) -> Result<Vec<Option<i32>>> {
+    track_bssid_with(bssid, samples, interval, scan)
+}
+
+fn track_bssid_with<F>(
+    bssid: &str,
+    samples: usize,
+    interval: Duration,
+    mut scan: F,
+) -> Result<Vec<Option<i32>>>
+where
+    F: FnMut() -> Result<Vec<Wifi>>,
+{
+    let bssid = normalize_mac(bssid);
+    let mut signals = Vec::with_capacity(samples);
+    for sample in 0..samples {
+        if sample > 0 {
+            thread::sleep(interval);
+        }
+        let signal = scan()?
+            .iter()
+            .find(|wifi| normalize_mac(&wifi.mac) == bssid)
+            .and_then(Wifi::signal_dbm);
+        signals.push(signal);
+    }
+    Ok(signals)
+}
+
+/// Scans every `poll` until a hotspot named `ssid` shows up, e.g. waiting for an access
+/// point you just brought up before connecting to it. Returns the hotspot with the
+/// strongest signal when several advertise `ssid`.
+///
+/// Fails with `Error::Timeout` when `ssid` wasn't found within `timeout`, and with the
+/// error of the first scan that fails. Scans take a few seconds on most platforms, so
+/// the last one may finish after `timeout`.
+pub fn wait_for_ssid(ssid: &str, timeout: Duration, poll: Duration) -> Result<Wifi> {
+    wait_for_ssid_with(ssid, timeout, poll, scan)
+}
+
+fn wait_for_ssid_with<F>(ssid: &str, timeout: Duration, poll: Duration, mut scan: F) -> Result<Wifi>
+where
+    F: FnMut() -> Result<Vec<Wifi>>,
+{
+    let start = Instant::now();
+    loop {
+        if let Some(wifi) = scan()?
+            .into_iter()
+            .filter(|wifi| wifi.ssid == ssid)
+            .max_by_key(Wifi::signal_dbm)
+        {
+            return Ok(wifi);
+        }
+        if start.elapsed() + poll > timeout {
+            return Err(Error::Timeout);
+        }
+        thread::sleep(poll);
+    }
+}
+
+/// Returns the hotspot you are currently connected to, or `None` if you aren't connected.
+/// Uses `airport -I` on macOS, `iw dev <interface> link` on Linux and
+/// `netsh wlan show interfaces` on Windows.
+///
+/// Versions of macOS without a working `airport` (14.4 and later) use `wdutil info`
+/// instead, which needs root: without it this fails with `Error::NeedsRoot`.
+///
+/// Only the fields reported by the platform's tool are filled in, e.g. the Linux
+/// version of the lib leaves `security` empty.
+pub fn connected_network() -> Result<Option<Wifi>> {
+    crate::sys::connected_network()
+}
+
+/// Returns the hotspot you are currently connected to along with the bitrates of the
+/// connection, or `None` if you aren't connected. Uses the same commands as
+/// `connected_network`.
+pub fn connected_link_info() -> Result<Option<LinkInfo>> {
+    crate::sys::connected_link_info()
+}
+
+/// Returns the names of the WiFi networks saved in NetworkManager, sorted and without
+/// duplicates, so scanned hotspots can be marked as "saved". Uses
+/// `nmcli -t -f name,type connection show`.
+///
+/// Only available on Linux, and only finds networks configured with NetworkManager.
+/// These are the names of the connection profiles, which are the network's SSID
+/// unless the profile was renamed.
+#[cfg(target_os = "linux")]
+pub fn known_networks() -> Result<Vec<String>> {
+    crate::sys::known_networks()
+}
+
+/// Returns whether the WiFi radios are turned off, the usual reason for a scan finding
+/// nothing on a laptop in airplane mode. Uses `rfkill list wifi`.
+///
+/// Only available on Linux. With several radios, the status of the least blocked one is
+/// returned, as scanning works as long as one isn't blocked.
+#[cfg(target_os = "linux")]
+pub fn radio_status() -> Result<RadioStatus> {
+    crate::sys::radio_status()
+}
+
+/// Returns the signal in dBm of the hotspot you are currently connected to, or `None`
+/// if you aren't connected, e.g. for a live signal meter. Uses the same commands as
+/// `connected_network`, which are much quicker than a scan and don't disrupt the
+/// connection.
+///
+/// On Windows the signal is converted from `netsh`'s percentage with the default
+/// `SignalBounds`.
+pub fn current_link_signal() -> Result<Option<i32>> {
+    Ok(connected_network()?.and_then(|wifi| wifi.signal_dbm()))
+}
+
+/// Returns the external commands `scan` runs on this platform, with their arguments,
+/// without running them, e.g. to audit what this crate invokes or to allowlist the
+/// commands in a locked down environment.
+///
+/// Fallbacks are listed in the order they're tried, later commands only run when an
+/// earlier tool isn't installed. On Linux `<interface>` stands for the interface found
+/// by `iw dev`, and the `dbus` and `netlink` features are tried first without running
+/// any commands.
+pub fn scan_commands() -> Vec<String> {
+    crate::sys::scan_commands(&ScanOptions::default())
+}
+
+/// Lists the WiFi interfaces of this machine along with the hotspot each is connected
+/// to. Uses `iw dev` on Linux, `networksetup` and `airport -I` on macOS and
+/// `netsh wlan show interfaces` on Windows.
+pub fn show_interfaces() -> Result<Vec<Interface>> {
+    crate::sys::show_interfaces()
+}
+
+/// Lowercases a mac address and pads each octet to two digits, as `airport` drops
+/// leading zeros (e.g. `0:35:1a:90:56:3`).
+pub(crate) fn normalize_mac(mac: &str) -> String {
+    mac.trim()
+        .split(':')
+        .map(|octet| format!("{:0>2}", octet.to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wifi(mac: &str, ssid: &str, signal_level: &str) -> Wifi {
+        Wifi {
+            mac: mac.to_string(),
+            ssid: ssid.to_string(),
+            signal_level: signal_level.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_track_bssid() {
+        let mut scans = vec![
+            Ok(vec![]),
+            Ok(vec![wifi("0:35:1A:90:56:3", "", "-70")]),
+            Ok(vec![wifi("00:35:1a:90:56:03", "", "-61.00")]),
+        ]
+        .into_iter();
+
+        assert_eq!(
+            Ok(vec![None, Some(-70), Some(-61)]),
+            track_bssid_with("00:35:1a:90:56:03", 3, Duration::ZERO, || {
+                scans.next().unwrap()
+            })
+        );
+        assert_eq!(
+            Err(Error::Cancelled),
+            track_bssid_with("00:35:1a:90:56:03", 2, Duration::ZERO, || {
+                Err(Error::Cancelled)
+            })
+        );
+    }
+
+    #[test]
+    fn should_wait_for_ssid() {
+        let mut scans = vec![
+            Ok(vec![wifi("00:00:00:00:00:01", "other", "-40")]),
+            Ok(vec![
+                wifi("00:00:00:00:00:02", "hello", "-70"),
+                wifi("00:00:00:00:00:03", "hello", "-55"),
+            ]),
+        ]
+        .into_iter();
+        let timeout = Duration::from_secs(60);
+
+        assert_eq!(
+            Ok(wifi("00:00:00:00:00:03", "hello", "-55")),
+            wait_for_ssid_with("hello", timeout, Duration::ZERO, || {
+                scans.next().unwrap()
+            })
+        );
+        assert_eq!(
+            Err(Error::Timeout),
+            wait_for_ssid_with("hello", Duration::ZERO, Duration::from_secs(1), || {
+                Ok(vec![])
+            })
+        );
+        assert_eq!(
+            Err(Error::Cancelled),
+            wait_for_ssid_with("hello", timeout, Duration::ZERO, || {
+                Err(Error::Cancelled)
+            })
+        );
+    }
+
+    #[test]
+    fn should_merge_passes() {
+        let first = vec![
+            wifi("00:00:00:00:00:01", "", "-70"),
+            wifi("00:00:00:00:00:02", "", "-50"),
+        ];
+        let second = vec![
+            wifi("00:00:00:00:00:03", "", "-80"),
+            wifi("00:00:00:00:00:01", "", "-60"),
+            wifi("00:00:00:00:00:02", "", "-55"),
+        ];
+
+        assert_eq!(
+            vec![
+                wifi("00:00:00:00:00:02", "", "-50"),
+                wifi("00:00:00:00:00:03", "", "-80"),
+                wifi("00:00:00:00:00:01", "", "-60"),
+            ],
+            merge_passes(vec![first.clone(), second])
+        );
+        assert_eq!(first, merge_passes(vec![first.clone()]));
+    }
+
+    #[test]
+    fn should_normalize_mac() {
+        assert_eq!("00:35:1a:90:56:03", normalize_mac("0:35:1A:90:56:3"));
+        assert_eq!("ab:cd:ef:01:23:45", normalize_mac("ab:cd:ef:01:23:45"));
+    }
+
+    #[test]
+    fn should_parse_signal_dbm() {
+        let mut wifi = Wifi::default();
+        assert_eq!(None, wifi.signal_dbm());
+
+        wifi.signal_level = "-67.00".to_string();
+        assert_eq!(Some(-67), wifi.signal_dbm());
+        assert_eq!(Some(66), wifi.quality_percent());
+
+        wifi.signal_level = "-70".to_string();
+        assert_eq!(Some(-70), wifi.signal_dbm());
+    }
+
+    #[test]
+    fn should_convert_signal_to_milliwatts() {
+        let mut wifi = Wifi::default();
+        assert_eq!(None, wifi.signal_milliwatts());
+
+        wifi.signal_level = "-30".to_string();
+        assert!((wifi.signal_milliwatts().unwrap() - 0.001).abs() < 1e-12);
+
+        wifi.signal_level = "0.00".to_string();
+        assert_eq!(Some(1.0), wifi.signal_milliwatts());
+    }
+
+    #[test]
+    fn should_label_signal() {
+        let mut wifi = Wifi::default();
+        assert_eq!(SignalLabel::Unknown, wifi.signal_label());
+
+        wifi.signal_level = "-65.00".to_string();
+        assert_eq!(SignalLabel::Fair, wifi.signal_label());
+        assert_eq!(
+            SignalLabel::Good,
+            wifi.signal_label_with(SignalThresholds {
+                excellent_dbm: -55,
+                good_dbm: -65,
+                fair_dbm: -75,
+            })
+        );
+    }
+
+    #[test]
+    fn should_estimate_distance() {
+        let mut wifi = Wifi::default();
+        assert_eq!(None, wifi.estimated_distance_m(-40));
+
+        wifi.signal_level = "-40".to_string();
+        assert_eq!(Some(1.0), wifi.estimated_distance_m(-40));
+
+        wifi.signal_level = "-70.00".to_string();
+        assert_eq!(Some(10.0), wifi.estimated_distance_m(-40));
+        assert_eq!(
+            Some(1000.0),
+            wifi.estimated_distance_m_with_exponent(-40, 1.0)
+        );
+        assert_eq!(None, wifi.estimated_distance_m_with_exponent(-40, 0.0));
+    }
+
+    #[test]
+    fn should_detect_hidden() {
+        let mut wifi = Wifi::default();
+        assert!(wifi.is_hidden());
+
+        wifi.ssid = "\\x00\\x00\\x00".to_string();
+        assert!(wifi.is_hidden());
+
+        wifi.ssid = "hello".to_string();
+        assert!(!wifi.is_hidden());
+    }
+
+    #[test]
+    fn should_detect_enterprise() {
+        let mut wifi = Wifi {
+            security: "WPA2-Enterprise".to_string(),
+            ..Default::default()
+        };
+        assert!(wifi.is_enterprise());
+
+        wifi.security = "WPA2-Personal".to_string();
+        assert!(!wifi.is_enterprise());
+
+        // the 802.1X suites `iw` reports
+        wifi.akm_suites = vec!["PSK".to_string(), "IEEE 802.1X".to_string()];
+        assert!(wifi.is_enterprise());
+        wifi.akm_suites = vec!["FT/IEEE 802.1X".to_string()];
+        assert!(wifi.is_enterprise());
+        wifi.akm_suites = vec!["SAE".to_string()];
+        assert!(!wifi.is_enterprise());
+    }
+
+    #[test]
+    fn should_anonymize_mac() {
+        let wifi = Wifi {
+            mac: "0:35:1A:90:56:3".to_string(),
+            ssid: "hello".to_string(),
+            ..Default::default()
+        };
+
+        let anonymized = wifi.anonymized();
+        assert_eq!("00:35:1a:xx:xx:xx", anonymized.mac);
+        assert_eq!("hello", anonymized.ssid);
+        assert_eq!("xx:xx:xx:xx:xx:xx", wifi.anonymized_with(false).mac);
+        assert_eq!("", Wifi::default().anonymized().mac);
+    }
+
+    #[test]
+    fn should_detect_dfs_channel() {
+        let wifi = |channel: &str, frequency| Wifi {
+            channel: channel.to_string(),
+            frequency,
+            ..Default::default()
+        };
+        assert!(wifi("100", None).is_dfs_channel());
+        assert!(wifi("52,+1", None).is_dfs_channel());
+        assert!(!wifi("36", None).is_dfs_channel());
+        assert!(!wifi("149", Some(5745)).is_dfs_channel());
+        // channel 53 in the 6 GHz band
+        assert!(!wifi("53", Some(6215)).is_dfs_channel());
+        assert!(!wifi("", None).is_dfs_channel());
+    }
+
+    #[test]
+    fn should_match_frequency() {
+        let mut wifi = Wifi {
+            channel: "1".to_string(),
+            ..Default::default()
+        };
+        assert!(wifi.is_on_frequency(2412));
+        assert!(!wifi.is_on_frequency(2437));
+        // channel 1 in the 6 GHz band
+        assert!(!wifi.is_on_frequency(5955));
+
+        wifi.frequency = Some(5955);
+        assert!(wifi.is_on_frequency(5955));
+        assert!(!wifi.is_on_frequency(2412));
+    }
+
+    #[test]
+    fn should_reject_invalid_frequency() {
+        let options = ScanOptions::new().frequencies(vec![2412, 2413]);
+        assert_eq!(
+            Error::InvalidFrequency(2413),
+            scan_with_options(&options).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_reg_domain() {
+        let options = ScanOptions::new().set_reg_domain("DEU");
+        assert_eq!(
+            Error::InvalidRegDomain("DEU".to_string()),
+            scan_with_options(&options).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn should_label_populated_fields() {
+        let wifi = Wifi {
+            mac: "11:22:33:44:55:66".to_string(),
+            ssid: "hello".to_string(),
+            channel: "36".to_string(),
+            signal_level: "-67".to_string(),
+            security: "WPA2-Personal".to_string(),
+            frequency: Some(5180),
+            akm_suites: vec!["PSK".to_string(), "SAE".to_string()],
+            qos_enabled: true,
+            channel_utilization: Some(51),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            "MAC:                 11:22:33:44:55:66\n\
+             SSID:                hello\n\
+             Channel:             36\n\
+             Band:                5 GHz\n\
+             Frequency:           5180 MHz\n\
+             Signal:              -67 dBm\n\
+             Security:            WPA2-Personal\n\
+             AKM suites:          PSK, SAE\n\
+             QoS (WMM):           yes\n\
+             Channel utilization: 20%\n",
+            wifi.detail_string()
+        );
+        assert_eq!("", Wifi::default().detail_string());
+    }
+
+    #[test]
+    fn should_debug_populated_fields_only() {
+        let wifi = Wifi {
+            mac: "11:22:33:44:55:66".to_string(),
+            ssid: "hello".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            r#"Wifi { mac: "11:22:33:44:55:66", ssid: "hello" }"#,
+            format!("{:?}", wifi)
+        );
+        assert_eq!(
+            r#"Wifi { mac: "11:22:33:44:55:66", ssid: "hello", channel: "", signal_level: "", security: "", radio_type: None, frequency: None, snr_db: None, last_seen_ms_ago: None, capabilities: [], ssid_raw: [], akm_suites: [], spatial_streams: None, secondary_channel_offset: None, bss_color: None, qos_enabled: false, station_count: None, channel_utilization: None, vendor_ies: [], encryption: None, max_rate_mbps: None, connected: false }"#,
+            format!("{:?}", wifi.full_debug())
+        );
+    }
 }
@@ -0,0 +1,19 @@
+use crate::Wifi;
+
+/// Details of the current connection, see `connected_link_info`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LinkInfo {
+    /// the hotspot you are connected to, as returned by `connected_network`, use
+    /// `Wifi::signal_dbm` for the signal of the connection
+    pub network: Wifi,
+    /// rate data is sent at in Mbit/s
+    pub tx_bitrate_mbps: Option<f64>,
+    /// rate data is received at in Mbit/s, not reported on macOS
+    pub rx_bitrate_mbps: Option<f64>,
+}
+
+/// Parses a bitrate in Mbit/s, ignoring anything after the number, e.g. `iw`'s
+/// `72.2 MBit/s MCS 7 short GI`.
+pub(crate) fn parse_bitrate(bitrate: &str) -> Option<f64> {
+    bitrate.split_whitespace().next()?.parse().ok()
+}
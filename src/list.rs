@@ -0,0 +1,203 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use crate::{connected_network, normalize_mac, Band, Result, Wifi};
+
+/// Hotspots returned by `scan_list`, with methods to query them.
+///
+/// Derefs to `[Wifi]`, so it can be iterated over and passed to functions taking a
+/// slice, e.g. `connectable(&list, -80, false)`. Filtering and sorting methods take
+/// `self` so they can be chained:
+///
+/// ```no_run
+/// # fn main() -> Result<(), wifiscanner::Error> {
+/// let nearby = wifiscanner::scan_list()?
+///     .dedupe()
+///     .by_band(wifiscanner::Band::FiveGhz)
+///     .sorted_by_signal();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WifiList(pub Vec<Wifi>);
+
+impl WifiList {
+    /// Returns the hotspot with the strongest signal, see `strongest`.
+    pub fn strongest(&self) -> Option<&Wifi> {
+        self.0.iter().max_by_key(|wifi| wifi.signal_dbm())
+    }
+
+    /// Keeps only the hotspots on `band`, dropping those whose channel couldn't be
+    /// parsed.
+    pub fn by_band(self, band: Band) -> WifiList {
+        self.filter(|wifi| {
+            wifi.channel_info()
+                .is_some_and(|channel| channel.band == band)
+        })
+    }
+
+    /// Removes hotspots reported more than once (e.g. by several interfaces), keeping
+    /// the strongest signal for each mac address. Otherwise the order is unchanged.
+    pub fn dedupe(self) -> WifiList {
+        let mut strongest: HashMap<String, usize> = HashMap::new();
+        for (index, wifi) in self.0.iter().enumerate() {
+            let best = strongest.entry(normalize_mac(&wifi.mac)).or_insert(index);
+            if wifi.signal_dbm() > self.0[*best].signal_dbm() {
+                *best = index;
+            }
+        }
+
+        WifiList(
+            self.0
+                .into_iter()
+                .enumerate()
+                .filter(|(index, wifi)| strongest[&normalize_mac(&wifi.mac)] == *index)
+                .map(|(_, wifi)| wifi)
+                .collect(),
+        )
+    }
+
+    /// Sorts the hotspots strongest signal first, hotspots whose signal couldn't be
+    /// parsed go last.
+    pub fn sorted_by_signal(mut self) -> WifiList {
+        self.0
+            .sort_by_key(|wifi| (wifi.signal_dbm().is_none(), Reverse(wifi.signal_dbm())));
+        self
+    }
+
+    /// Returns the hotspot in the list you are currently connected to, using
+    /// `connected_network` to find it. Note this runs a command every time.
+    pub fn connected(&self) -> Result<Option<&Wifi>> {
+        Ok(connected_network()?.and_then(|connected| {
+            let connected_mac = normalize_mac(&connected.mac);
+            self.0
+                .iter()
+                .find(|wifi| normalize_mac(&wifi.mac) == connected_mac)
+        }))
+    }
+
+    /// Returns the hotspots keyed by their mac address for lookups by BSSID. Keys are
+    /// lowercase with `airport`'s missing leading zeros added back, e.g.
+    /// `00:35:1a:90:56:03`. Hotspots reported more than once are kept once with their
+    /// strongest signal, as with `dedupe`, or the first one reported if their signals
+    /// are equal.
+    pub fn into_map(self) -> HashMap<String, Wifi> {
+        self.dedupe()
+            .into_iter()
+            .map(|wifi| (normalize_mac(&wifi.mac), wifi))
+            .collect()
+    }
+
+    /// Returns the hotspots as a `Vec`.
+    pub fn into_vec(self) -> Vec<Wifi> {
+        self.0
+    }
+
+    fn filter<F: FnMut(&Wifi) -> bool>(mut self, predicate: F) -> WifiList {
+        self.0.retain(predicate);
+        self
+    }
+}
+
+impl Deref for WifiList {
+    type Target = [Wifi];
+
+    fn deref(&self) -> &[Wifi] {
+        &self.0
+    }
+}
+
+impl From<Vec<Wifi>> for WifiList {
+    fn from(wifis: Vec<Wifi>) -> WifiList {
+        WifiList(wifis)
+    }
+}
+
+impl IntoIterator for WifiList {
+    type Item = Wifi;
+    type IntoIter = std::vec::IntoIter<Wifi>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wifi(mac: &str, channel: &str, signal_level: &str) -> Wifi {
+        Wifi {
+            mac: mac.to_string(),
+            channel: channel.to_string(),
+            signal_level: signal_level.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn macs(list: &WifiList) -> Vec<&str> {
+        list.iter().map(|wifi| wifi.mac.as_str()).collect()
+    }
+
+    fn list() -> WifiList {
+        WifiList(vec![
+            wifi("00:00:00:00:00:01", "1", "-70"),
+            wifi("00:00:00:00:00:02", "36", "-50.00"),
+            wifi("00:00:00:00:00:03", "6", ""),
+            wifi("0:0:0:0:0:1", "1", "-60"),
+            wifi("00:00:00:00:00:04", "149", "-80"),
+        ])
+    }
+
+    #[test]
+    fn should_find_strongest() {
+        assert_eq!("00:00:00:00:00:02", list().strongest().unwrap().mac);
+        assert_eq!(None, WifiList::default().strongest());
+    }
+
+    #[test]
+    fn should_filter_by_band() {
+        assert_eq!(
+            vec!["00:00:00:00:00:02", "00:00:00:00:00:04"],
+            macs(&list().by_band(Band::FiveGhz))
+        );
+    }
+
+    #[test]
+    fn should_dedupe_keeping_strongest() {
+        assert_eq!(
+            vec![
+                "00:00:00:00:00:02",
+                "00:00:00:00:00:03",
+                "0:0:0:0:0:1",
+                "00:00:00:00:00:04"
+            ],
+            macs(&list().dedupe())
+        );
+    }
+
+    #[test]
+    fn should_key_map_by_normalized_mac() {
+        let map = list().into_map();
+        assert_eq!(4, map.len());
+        assert_eq!("-60", map["00:00:00:00:00:01"].signal_level);
+        assert_eq!("0:0:0:0:0:1", map["00:00:00:00:00:01"].mac);
+        assert_eq!("36", map["00:00:00:00:00:02"].channel);
+        assert!(map.contains_key("00:00:00:00:00:03"));
+    }
+
+    #[test]
+    fn should_sort_by_signal() {
+        assert_eq!(
+            vec![
+                "00:00:00:00:00:02",
+                "0:0:0:0:0:1",
+                "00:00:00:00:00:01",
+                "00:00:00:00:00:04",
+                "00:00:00:00:00:03"
+            ],
+            macs(&list().sorted_by_signal())
+        );
+    }
+}
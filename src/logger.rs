@@ -0,0 +1,165 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::output::{write_rows, CSV_HEADER};
+use crate::{OutputFormat, Result, Wifi};
+
+/// Scans being logged to a file in the background, see `log_scans_to_file`.
+///
+/// Dropping the logger stops it after the scan in progress, without waiting for it.
+#[derive(Debug)]
+pub struct ScanLogger {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ScanLogger {
+    /// Size in bytes `log_scans_to_file` lets the log grow to before rotating it.
+    pub const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+    /// Runs `scan` every `interval` on a new thread, appending its hotspots to `path`.
+    pub(crate) fn spawn<F>(
+        mut scan: F,
+        path: PathBuf,
+        interval: Duration,
+        format: OutputFormat,
+        max_bytes: u64,
+    ) -> ScanLogger
+    where
+        F: FnMut() -> Result<Vec<Wifi>> + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = stop.clone();
+        let thread = thread::spawn(move || {
+            while !flag.load(Ordering::Relaxed) {
+                let start = Instant::now();
+                // a failed scan or write shouldn't end an unattended survey
+                match scan() {
+                    Ok(wifis) => {
+                        if let Err(err) = append(&path, &wifis, format, max_bytes) {
+                            eprintln!("wifiscanner: writing {} failed: {}", path.display(), err);
+                        }
+                    }
+                    Err(err) => eprintln!("wifiscanner: scan failed: {}", err),
+                }
+                // sleep in slices, so stopping doesn't wait out a long interval
+                while start.elapsed() < interval && !flag.load(Ordering::Relaxed) {
+                    thread::sleep((interval - start.elapsed()).min(Duration::from_millis(50)));
+                }
+            }
+        });
+
+        ScanLogger {
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Stops logging, blocking until the scan in progress has been written.
+    pub fn stop(mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for ScanLogger {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Appends `wifis` to the log at `path`, first moving a log of at least `max_bytes`
+/// to `<path>.1` (replacing the previous one) so it starts afresh. New CSV logs get a
+/// header row.
+fn append(path: &Path, wifis: &[Wifi], format: OutputFormat, max_bytes: u64) -> io::Result<()> {
+    let len = fs::metadata(path).map_or(0, |metadata| metadata.len());
+    let len = if len >= max_bytes {
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(".1");
+        fs::rename(path, rotated)?;
+        0
+    } else {
+        len
+    };
+
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut writer = io::BufWriter::new(file);
+    if len == 0 && format == OutputFormat::Csv {
+        writeln!(writer, "{}", CSV_HEADER)?;
+    }
+    write_rows(&mut writer, wifis, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    fn log_path(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("wifiscanner-{}-{}", process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn wifis() -> Vec<Wifi> {
+        vec![Wifi {
+            mac: "11:22:33:44:55:66".to_string(),
+            ssid: "hello".to_string(),
+            ..Default::default()
+        }]
+    }
+
+    #[test]
+    fn should_append_and_rotate_log() {
+        let path = log_path("rotate.csv");
+        let rotated = log_path("rotate.csv.1");
+
+        append(&path, &wifis(), OutputFormat::Csv, 1024).unwrap();
+        append(&path, &wifis(), OutputFormat::Csv, 1024).unwrap();
+        let log = fs::read_to_string(&path).unwrap();
+        assert_eq!(1, log.matches(CSV_HEADER).count());
+        assert_eq!(3, log.lines().count());
+
+        // the log is now over the limit, so it's rotated before the next write
+        append(&path, &wifis(), OutputFormat::Csv, 10).unwrap();
+        assert_eq!(log, fs::read_to_string(&rotated).unwrap());
+        assert_eq!(2, fs::read_to_string(&path).unwrap().lines().count());
+
+        fs::remove_file(path).unwrap();
+        fs::remove_file(rotated).unwrap();
+    }
+
+    #[test]
+    fn should_keep_logging_after_errors() {
+        let path = log_path("errors.jsonl");
+        let mut scans = 0;
+        let logger = ScanLogger::spawn(
+            move || {
+                scans += 1;
+                if scans % 2 == 0 {
+                    Err(crate::Error::CommandNotFound)
+                } else {
+                    Ok(wifis())
+                }
+            },
+            path.clone(),
+            Duration::from_millis(1),
+            OutputFormat::JsonLines,
+            ScanLogger::DEFAULT_MAX_BYTES,
+        );
+
+        while fs::read_to_string(&path).map_or(0, |log| log.lines().count()) < 2 {
+            thread::sleep(Duration::from_millis(5));
+        }
+        logger.stop();
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,413 @@
+use std::cmp::Reverse;
+use std::ops::BitOr;
+use std::time::Duration;
+
+use crate::{Band, SignalBounds, Wifi};
+
+/// Options controlling which hotspots `scan_with_options` returns.
+///
+/// Options are set using the builder methods, e.g.
+///
+/// ```
+/// let options = wifiscanner::ScanOptions::new().exclude_connected(true);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanOptions {
+    pub(crate) fields: FieldSet,
+    pub(crate) exclude_connected: bool,
+    pub(crate) frequencies: Vec<u32>,
+    pub(crate) band: Option<Band>,
+    pub(crate) sort_by: Option<SortKey>,
+    pub(crate) signal_bounds: SignalBounds,
+    pub(crate) require_wifi_hardware: bool,
+    pub(crate) strict_utf8: bool,
+    pub(crate) min_interval: Option<Duration>,
+    pub(crate) vendor_ies: bool,
+    pub(crate) passes: u32,
+    pub(crate) reg_domain: Option<String>,
+    pub(crate) restore_reg_domain: bool,
+    pub(crate) merge_nmcli: bool,
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub(crate) require_radio_unblocked: bool,
+    pub(crate) limit: Option<usize>,
+}
+
+impl ScanOptions {
+    /// Creates options matching the behaviour of `scan`.
+    pub fn new() -> ScanOptions {
+        ScanOptions::default()
+    }
+
+    /// Returns the frequencies in MHz backends able to restrict the scan should scan,
+    /// empty for every channel.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub(crate) fn scan_frequencies(&self) -> Vec<u32> {
+        match self.band {
+            Some(band) if self.frequencies.is_empty() => band.frequencies(),
+            Some(band) => self
+                .frequencies
+                .iter()
+                .copied()
+                .filter(|&frequency| Band::from_frequency(frequency) == Some(band))
+                .collect(),
+            None => self.frequencies.clone(),
+        }
+    }
+
+    /// Drops the hotspot you are currently connected to from the results, so only
+    /// "other" networks are listed. The connected hotspot is found using
+    /// `connected_network` and matched on its mac address.
+    ///
+    /// This is a no-op when you aren't connected to a hotspot, or when the connected
+    /// hotspot can't be found, e.g. on macOS without root where only `wdutil` reports it.
+    pub fn exclude_connected(mut self, exclude: bool) -> ScanOptions {
+        self.exclude_connected = exclude;
+        self
+    }
+
+    /// Only scans the given frequencies in MHz (e.g. `2412` for channel 1), which is
+    /// much quicker than scanning every channel.
+    ///
+    /// `iw` and the `netlink` feature restrict the scan itself, other platforms still
+    /// scan every channel and the results are filtered instead. Results without a
+    /// `frequency` are matched on their channel number, so 6 GHz frequencies only
+    /// match results that report a `frequency`.
+    ///
+    /// Scanning fails with `Error::InvalidFrequency` if a frequency isn't a 2.4, 5 or
+    /// 6 GHz WiFi channel.
+    pub fn frequencies(mut self, frequencies: Vec<u32>) -> ScanOptions {
+        self.frequencies = frequencies;
+        self
+    }
+
+    /// Only returns hotspots on `band`, e.g. for a quicker 5 GHz only survey.
+    ///
+    /// How much quicker depends on the backend:
+    ///
+    /// - `iw` and the `netlink` feature only scan the band's channels. Drivers that
+    ///   reject a channel they don't support make `iw` fail, in which case every
+    ///   channel is scanned again and the results are filtered.
+    /// - `iwlist`, `wpa_cli`, the `dbus` feature, `airport` and `netsh` can't restrict
+    ///   the scan, every channel is scanned and the results are filtered.
+    ///
+    /// Hotspots whose band can't be told from their channel are dropped. Combined with
+    /// `frequencies`, only the frequencies on `band` are scanned.
+    pub fn band(mut self, band: Band) -> ScanOptions {
+        self.band = Some(band);
+        self
+    }
+
+    /// Sets the bounds used to convert the signal percentages reported by `netsh` and
+    /// NetworkManager to dBm, see `SignalBounds`.
+    pub fn signal_bounds(mut self, bounds: SignalBounds) -> ScanOptions {
+        self.signal_bounds = bounds;
+        self
+    }
+
+    /// Fails with `Error::NoWirelessInterface` before scanning if `has_wifi_hardware`
+    /// doesn't find any WiFi hardware, rather than with whatever error the platform's
+    /// tool gives.
+    pub fn require_wifi_hardware(mut self, require: bool) -> ScanOptions {
+        self.require_wifi_hardware = require;
+        self
+    }
+
+    /// Fails with `Error::RadioBlocked` before scanning if `radio_status` finds the WiFi
+    /// radios turned off, rather than returning no hotspots. When `rfkill` isn't
+    /// installed the scan goes ahead.
+    ///
+    /// Only checked on Linux.
+    pub fn require_radio_unblocked(mut self, require: bool) -> ScanOptions {
+        self.require_radio_unblocked = require;
+        self
+    }
+
+    /// Fails with `Error::InvalidUtf8` if the output of the platform's tool isn't valid
+    /// UTF-8, e.g. because of the locale's encoding. By default invalid bytes are
+    /// replaced with `U+FFFD`, which can silently mangle SSIDs.
+    ///
+    /// Only the scanning tools' output is checked, the `dbus` and `netlink` features
+    /// don't parse any text. Use `Wifi::ssid_raw` for SSIDs that aren't valid UTF-8.
+    pub fn strict_utf8(mut self, strict: bool) -> ScanOptions {
+        self.strict_utf8 = strict;
+        self
+    }
+
+    /// Scans at most once per `interval`: when `scan_with_options` was last called less
+    /// than `interval` ago, the hotspots that scan found are returned again instead of
+    /// scanning. Protects the radio, and your connection, from callers polling in a
+    /// tight loop.
+    ///
+    /// The last scan is shared by every call to `scan_with_options` in the process, but
+    /// only reused by calls with the same options for the scan itself, e.g. the same
+    /// `frequencies`, `band` or `passes`. Only the hotspots found are reused, the
+    /// filtering and sorting of these options still apply. Failed scans don't count.
+    pub fn min_interval(mut self, interval: Duration) -> ScanOptions {
+        self.min_interval = Some(interval);
+        self
+    }
+
+    /// Scans `passes` times in a row and merges the results, as a single scan can miss
+    /// hotspots whose beacons it didn't catch on their channel, e.g. in crowded areas.
+    /// Hotspots found more than once are kept once, with their strongest signal.
+    ///
+    /// Defaults to one pass, `0` is taken as one too. Each pass takes as long as a
+    /// scan, a few seconds on most platforms. With `min_interval` the passes together
+    /// count as one scan.
+    pub fn passes(mut self, passes: u32) -> ScanOptions {
+        self.passes = passes;
+        self
+    }
+
+    /// Fills in `Wifi::vendor_ies` with the raw vendor specific information elements,
+    /// e.g. to decode WPS or mesh extensions yourself. Off by default as most callers
+    /// don't need them.
+    ///
+    /// Only `iw` on Linux reports them, with other backends they stay empty.
+    pub fn vendor_ies(mut self, include: bool) -> ScanOptions {
+        self.vendor_ies = include;
+        self
+    }
+
+    /// Only parses the optional `fields`, leaving the others at their default, which
+    /// speeds up scanning many times a second when only e.g. the SSIDs and signals are
+    /// needed. Every field is parsed by default. The mac address, SSID, channel, signal
+    /// and security are always parsed, so `FieldSet::empty()` gives just those.
+    ///
+    /// The effect depends on the backend:
+    ///
+    /// - `iw` skips the capability, BSS load and vendor specific information elements.
+    ///   `VENDOR_IES` also needs `vendor_ies` to be set.
+    /// - `nmcli` isn't asked for the `rate` field without `MAX_RATE`.
+    /// - `iwlist`, `wpa_cli`, the `dbus` and `netlink` features, `airport` and `netsh`
+    ///   parse their output as usual and return every field they report.
+    pub fn fields(mut self, fields: FieldSet) -> ScanOptions {
+        self.fields = fields;
+        self
+    }
+
+    /// Returns the `fields` to parse, without `VENDOR_IES` unless `vendor_ies` is set.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub(crate) fn parsed_fields(&self) -> FieldSet {
+        if self.vendor_ies {
+            self.fields
+        } else {
+            self.fields.difference(FieldSet::VENDOR_IES)
+        }
+    }
+
+    /// Returns these options without the ones only applied to the hotspots found, so
+    /// options scanning the same way compare equal, see `min_interval`.
+    pub(crate) fn scan_key(&self) -> ScanOptions {
+        ScanOptions {
+            exclude_connected: false,
+            sort_by: None,
+            min_interval: None,
+            limit: None,
+            ..self.clone()
+        }
+    }
+
+    /// Sets the regulatory domain to the country code `country` (e.g. `"DE"`, or `"00"`
+    /// for the world domain) with `iw reg set` before scanning, as some drivers don't
+    /// scan channels, like the 5 GHz DFS ones, the current domain doesn't allow.
+    ///
+    /// This changes the regulatory domain of the whole system, not just for the scan,
+    /// and needs root or `CAP_NET_ADMIN`, without which scanning fails with
+    /// `Error::CommandFailed`. The domain is left as set unless `restore_reg_domain` is
+    /// set. Scanning fails with `Error::InvalidRegDomain` if `country` isn't two
+    /// letters or `00`.
+    ///
+    /// Only used on Linux, other platforms manage the domain themselves.
+    pub fn set_reg_domain(mut self, country: &str) -> ScanOptions {
+        self.reg_domain = Some(country.to_string());
+        self
+    }
+
+    /// Puts the regulatory domain back to what it was after scanning with the one given
+    /// to `set_reg_domain`, rather than leaving it changed, even when the scan fails.
+    /// Scanning fails with `Error::FailedToParse`, before the domain is changed, if the
+    /// current one can't be read from `iw reg get`. Off by default.
+    pub fn restore_reg_domain(mut self, restore: bool) -> ScanOptions {
+        self.restore_reg_domain = restore;
+        self
+    }
+
+    /// Also lists the hotspots with `nmcli` after scanning with `iw` (or the tool it
+    /// falls back to) and merges the results by mac address, for the most complete
+    /// `Wifi`s on Linux: `iw` reports e.g. capabilities and BSS load, `nmcli` the
+    /// maximum rate.
+    ///
+    /// Fields already reported by `iw` are kept, those it left empty are filled in from
+    /// `nmcli`. Hotspots only one of them found are returned as that tool reported them.
+    /// When `nmcli` isn't installed or fails, `iw`'s results are returned as if this
+    /// weren't set. Ignored with the `dbus` and `netlink` features when they scan, and
+    /// on other platforms.
+    pub fn merge_nmcli(mut self, merge: bool) -> ScanOptions {
+        self.merge_nmcli = merge;
+        self
+    }
+
+    /// Sorts the results by `key`, by default they're returned in the order the
+    /// platform reports them, which varies from scan to scan.
+    pub fn sort_by(mut self, key: SortKey) -> ScanOptions {
+        self.sort_by = Some(key);
+        self
+    }
+
+    /// Returns at most `n` hotspots, e.g. for a UI only showing the strongest few. The
+    /// limit applies last, after the hotspots found by several `passes` are merged and
+    /// the results are filtered and sorted.
+    ///
+    /// Combine it with `sort_by(SortKey::Signal)` to keep the `n` strongest hotspots,
+    /// otherwise the first `n` in the order of `sort_by` are kept, or in the platform's
+    /// order when not sorting.
+    pub fn limit(mut self, n: usize) -> ScanOptions {
+        self.limit = Some(n);
+        self
+    }
+}
+
+/// Set of optional `Wifi` fields to parse, see `ScanOptions::fields`. Sets are combined
+/// with `|`, e.g. `FieldSet::CAPABILITIES | FieldSet::BSS_LOAD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldSet(u8);
+
+impl FieldSet {
+    /// `Wifi::capabilities`
+    pub const CAPABILITIES: FieldSet = FieldSet(1);
+    /// `Wifi::station_count` and `Wifi::channel_utilization`
+    pub const BSS_LOAD: FieldSet = FieldSet(1 << 1);
+    /// `Wifi::vendor_ies`
+    pub const VENDOR_IES: FieldSet = FieldSet(1 << 2);
+    /// `Wifi::max_rate_mbps`
+    pub const MAX_RATE: FieldSet = FieldSet(1 << 3);
+
+    /// Returns the set of none of the optional fields.
+    pub const fn empty() -> FieldSet {
+        FieldSet(0)
+    }
+
+    /// Returns the set of every field, the default.
+    pub const fn all() -> FieldSet {
+        FieldSet(
+            FieldSet::CAPABILITIES.0
+                | FieldSet::BSS_LOAD.0
+                | FieldSet::VENDOR_IES.0
+                | FieldSet::MAX_RATE.0,
+        )
+    }
+
+    /// Returns true if every field of `other` is in the set.
+    pub const fn contains(self, other: FieldSet) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the fields of the set that aren't in `other`.
+    pub const fn difference(self, other: FieldSet) -> FieldSet {
+        FieldSet(self.0 & !other.0)
+    }
+}
+
+impl Default for FieldSet {
+    fn default() -> FieldSet {
+        FieldSet::all()
+    }
+}
+
+impl BitOr for FieldSet {
+    type Output = FieldSet;
+
+    fn bitor(self, other: FieldSet) -> FieldSet {
+        FieldSet(self.0 | other.0)
+    }
+}
+
+/// Order of the results of `scan_with_options`, see `ScanOptions::sort_by`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// by mac address, ignoring case and `airport`'s missing leading zeros, so saved
+    /// results can be diffed
+    Bssid,
+    /// strongest signal first, hotspots whose signal couldn't be parsed go last
+    Signal,
+}
+
+impl SortKey {
+    /// Sorts `wifis`, keeping the platform's order for hotspots that compare equal.
+    pub(crate) fn sort(self, wifis: &mut [Wifi]) {
+        match self {
+            SortKey::Bssid => wifis.sort_by_cached_key(|wifi| crate::normalize_mac(&wifi.mac)),
+            SortKey::Signal => {
+                wifis.sort_by_key(|wifi| (wifi.signal_dbm().is_none(), Reverse(wifi.signal_dbm())))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wifi(mac: &str, ssid: &str) -> Wifi {
+        Wifi {
+            mac: mac.to_string(),
+            ssid: ssid.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_combine_field_sets() {
+        let fields = FieldSet::CAPABILITIES | FieldSet::BSS_LOAD;
+        assert!(fields.contains(FieldSet::BSS_LOAD));
+        assert!(!fields.contains(FieldSet::BSS_LOAD | FieldSet::MAX_RATE));
+        assert!(FieldSet::all().contains(fields));
+        assert!(!FieldSet::empty().contains(FieldSet::CAPABILITIES));
+        assert_eq!(
+            FieldSet::BSS_LOAD,
+            fields.difference(FieldSet::CAPABILITIES)
+        );
+
+        let options = ScanOptions::new();
+        assert_eq!(FieldSet::all(), options.fields);
+        assert_eq!(
+            FieldSet::all().difference(FieldSet::VENDOR_IES),
+            options.parsed_fields()
+        );
+        assert_eq!(FieldSet::all(), options.vendor_ies(true).parsed_fields());
+    }
+
+    #[test]
+    fn should_sort_by_bssid() {
+        let mut wifis = vec![
+            wifi("AB:CD:EF:01:23:45", "first"),
+            wifi("0:35:1a:90:56:3", "hello"),
+            wifi("ab:cd:ef:01:23:45", "second"),
+            wifi("00:35:1a:90:56:02", "world"),
+        ];
+        SortKey::Bssid.sort(&mut wifis);
+
+        let ssids: Vec<_> = wifis.iter().map(|wifi| wifi.ssid.as_str()).collect();
+        assert_eq!(vec!["world", "hello", "first", "second"], ssids);
+    }
+
+    #[test]
+    fn should_sort_by_signal() {
+        let mut wifis = vec![
+            Wifi {
+                signal_level: "-70".to_string(),
+                ..wifi("00:00:00:00:00:01", "weak")
+            },
+            wifi("00:00:00:00:00:02", "unknown"),
+            Wifi {
+                signal_level: "-50.00".to_string(),
+                ..wifi("00:00:00:00:00:03", "strong")
+            },
+        ];
+        SortKey::Signal.sort(&mut wifis);
+
+        let ssids: Vec<_> = wifis.iter().map(|wifi| wifi.ssid.as_str()).collect();
+        assert_eq!(vec!["strong", "weak", "unknown"], ssids);
+    }
+}
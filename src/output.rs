@@ -0,0 +1,267 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use crate::{Error, Result, Wifi};
+
+/// Format `scan_to_writer` writes hotspots in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// comma separated values with a header row, one hotspot per row
+    Csv,
+    /// one JSON object per line, see <https://jsonlines.org>
+    JsonLines,
+}
+
+pub(crate) const CSV_HEADER: &str =
+    "mac,ssid,channel,signal_level,security,radio_type,frequency,snr_db,\
+     last_seen_ms_ago,capabilities,ssid_raw,akm_suites,spatial_streams,\
+     secondary_channel_offset,bss_color,qos_enabled,station_count,channel_utilization,\
+     vendor_ies,encryption,max_rate_mbps,connected";
+
+/// Scans and writes the hotspots found to `writer` in `format`, flushing it afterwards.
+///
+/// Every field of `Wifi` is written, fields that aren't reported are left empty in CSV
+/// and are `null` in JSON. In CSV the `capabilities`, `akm_suites` and `vendor_ies` are
+/// separated by spaces. `ssid_raw` is written as lowercase hex in both formats.
+pub fn scan_to_writer<W: Write>(writer: &mut W, format: OutputFormat) -> Result<()> {
+    let wifis = crate::scan()?;
+    write_wifis(writer, &wifis, format).map_err(Error::from)
+}
+
+fn write_wifis<W: Write>(writer: &mut W, wifis: &[Wifi], format: OutputFormat) -> io::Result<()> {
+    if format == OutputFormat::Csv {
+        writeln!(writer, "{}", CSV_HEADER)?;
+    }
+    write_rows(writer, wifis, format)
+}
+
+/// Same as `write_wifis`, without the CSV header, e.g. to append to a log.
+pub(crate) fn write_rows<W: Write>(
+    writer: &mut W,
+    wifis: &[Wifi],
+    format: OutputFormat,
+) -> io::Result<()> {
+    for wifi in wifis {
+        let line = match format {
+            OutputFormat::Csv => csv_row(wifi),
+            OutputFormat::JsonLines => json_object(wifi),
+        };
+        writeln!(writer, "{}", line)?;
+    }
+    writer.flush()
+}
+
+fn csv_row(wifi: &Wifi) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    [
+        wifi.mac.clone(),
+        wifi.ssid.clone(),
+        wifi.channel.clone(),
+        wifi.signal_level.clone(),
+        wifi.security.clone(),
+        optional(wifi.radio_type.clone()),
+        optional(wifi.frequency.map(|value| value.to_string())),
+        optional(wifi.snr_db.map(|value| value.to_string())),
+        optional(wifi.last_seen_ms_ago.map(|value| value.to_string())),
+        wifi.capabilities.join(" "),
+        hex(&wifi.ssid_raw),
+        wifi.akm_suites.join(" "),
+        optional(wifi.spatial_streams.map(|value| value.to_string())),
+        optional(wifi.secondary_channel_offset.map(|value| value.to_string())),
+        optional(wifi.bss_color.map(|value| value.to_string())),
+        wifi.qos_enabled.to_string(),
+        optional(wifi.station_count.map(|value| value.to_string())),
+        optional(wifi.channel_utilization.map(|value| value.to_string())),
+        wifi.vendor_ies.join(" "),
+        optional(wifi.encryption.clone()),
+        optional(wifi.max_rate_mbps.map(|value| value.to_string())),
+        wifi.connected.to_string(),
+    ]
+    .iter()
+    .map(|value| csv_field(value))
+    .collect::<Vec<_>>()
+    .join(",")
+}
+
+/// Quotes a field if it contains a separator, quote or line break, as in RFC 4180.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn json_object(wifi: &Wifi) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "null".to_string());
+    let array = |values: &[String]| {
+        let values: Vec<_> = values.iter().map(|value| json_string(value)).collect();
+        format!("[{}]", values.join(","))
+    };
+
+    let fields = [
+        ("mac", json_string(&wifi.mac)),
+        ("ssid", json_string(&wifi.ssid)),
+        ("channel", json_string(&wifi.channel)),
+        ("signal_level", json_string(&wifi.signal_level)),
+        ("security", json_string(&wifi.security)),
+        (
+            "radio_type",
+            optional(wifi.radio_type.as_ref().map(|value| json_string(value))),
+        ),
+        (
+            "frequency",
+            optional(wifi.frequency.map(|value| value.to_string())),
+        ),
+        (
+            "snr_db",
+            optional(wifi.snr_db.map(|value| value.to_string())),
+        ),
+        (
+            "last_seen_ms_ago",
+            optional(wifi.last_seen_ms_ago.map(|value| value.to_string())),
+        ),
+        ("capabilities", array(&wifi.capabilities)),
+        ("ssid_raw", json_string(&hex(&wifi.ssid_raw))),
+        ("akm_suites", array(&wifi.akm_suites)),
+        (
+            "spatial_streams",
+            optional(wifi.spatial_streams.map(|value| value.to_string())),
+        ),
+        (
+            "secondary_channel_offset",
+            optional(wifi.secondary_channel_offset.map(|value| value.to_string())),
+        ),
+        (
+            "bss_color",
+            optional(wifi.bss_color.map(|value| value.to_string())),
+        ),
+        ("qos_enabled", wifi.qos_enabled.to_string()),
+        (
+            "station_count",
+            optional(wifi.station_count.map(|value| value.to_string())),
+        ),
+        (
+            "channel_utilization",
+            optional(wifi.channel_utilization.map(|value| value.to_string())),
+        ),
+        ("vendor_ies", array(&wifi.vendor_ies)),
+        (
+            "encryption",
+            optional(wifi.encryption.as_ref().map(|value| json_string(value))),
+        ),
+        (
+            "max_rate_mbps",
+            optional(wifi.max_rate_mbps.map(|value| value.to_string())),
+        ),
+        ("connected", wifi.connected.to_string()),
+    ];
+
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|(key, value)| format!("{}:{}", json_string(key), value))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wifis() -> Vec<Wifi> {
+        vec![
+            Wifi {
+                mac: "11:22:33:44:55:66".to_string(),
+                ssid: "hello".to_string(),
+                channel: "10".to_string(),
+                signal_level: "-67.00".to_string(),
+                security: "WPA2-Personal".to_string(),
+                frequency: Some(2457),
+                capabilities: vec!["ESS".to_string(), "Privacy".to_string()],
+                ssid_raw: b"hello".to_vec(),
+                akm_suites: vec!["PSK".to_string(), "SAE".to_string()],
+                spatial_streams: Some(2),
+                secondary_channel_offset: Some(-1),
+                bss_color: Some(5),
+                qos_enabled: true,
+                station_count: Some(3),
+                channel_utilization: Some(24),
+                vendor_ies: vec!["0050f204".to_string()],
+                encryption: Some("CCMP".to_string()),
+                max_rate_mbps: Some(270),
+                connected: true,
+                ..Default::default()
+            },
+            Wifi {
+                mac: "66:77:88:99:aa:bb".to_string(),
+                ssid: "say \"hi\", \\ bye".to_string(),
+                channel: "6".to_string(),
+                signal_level: "-70".to_string(),
+                security: "Open".to_string(),
+                radio_type: Some("802.11n".to_string()),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn should_write_csv() {
+        let mut output = Vec::new();
+        write_wifis(&mut output, &wifis(), OutputFormat::Csv).unwrap();
+
+        assert_eq!(
+            "mac,ssid,channel,signal_level,security,radio_type,frequency,snr_db,last_seen_ms_ago,capabilities,\
+             ssid_raw,akm_suites,spatial_streams,secondary_channel_offset,bss_color,qos_enabled,\
+             station_count,channel_utilization,vendor_ies,encryption,max_rate_mbps,connected\n\
+             11:22:33:44:55:66,hello,10,-67.00,WPA2-Personal,,2457,,,ESS Privacy,\
+             68656c6c6f,PSK SAE,2,-1,5,true,3,24,0050f204,CCMP,270,true\n\
+             66:77:88:99:aa:bb,\"say \"\"hi\"\", \\ bye\",6,-70,Open,802.11n,,,,,,,,,,false,,,,,,false\n",
+            String::from_utf8(output).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_write_json_lines() {
+        let mut output = Vec::new();
+        write_wifis(&mut output, &wifis(), OutputFormat::JsonLines).unwrap();
+
+        assert_eq!(
+            "{\"mac\":\"11:22:33:44:55:66\",\"ssid\":\"hello\",\"channel\":\"10\",\"signal_level\":\"-67.00\",\"security\":\"WPA2-Personal\",\"radio_type\":null,\"frequency\":2457,\"snr_db\":null,\"last_seen_ms_ago\":null,\"capabilities\":[\"ESS\",\"Privacy\"],\
+             \"ssid_raw\":\"68656c6c6f\",\"akm_suites\":[\"PSK\",\"SAE\"],\"spatial_streams\":2,\"secondary_channel_offset\":-1,\"bss_color\":5,\"qos_enabled\":true,\"station_count\":3,\"channel_utilization\":24,\
+             \"vendor_ies\":[\"0050f204\"],\"encryption\":\"CCMP\",\"max_rate_mbps\":270,\"connected\":true}\n\
+             {\"mac\":\"66:77:88:99:aa:bb\",\"ssid\":\"say \\\"hi\\\", \\\\ bye\",\"channel\":\"6\",\"signal_level\":\"-70\",\"security\":\"Open\",\"radio_type\":\"802.11n\",\"frequency\":null,\"snr_db\":null,\"last_seen_ms_ago\":null,\"capabilities\":[],\
+             \"ssid_raw\":\"\",\"akm_suites\":[],\"spatial_streams\":null,\"secondary_channel_offset\":null,\"bss_color\":null,\"qos_enabled\":false,\"station_count\":null,\"channel_utilization\":null,\
+             \"vendor_ies\":[],\"encryption\":null,\"max_rate_mbps\":null,\"connected\":false}\n",
+            String::from_utf8(output).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_escape_control_characters() {
+        assert_eq!("\"a\\u0000b\\n\"", json_string("a\0b\n"));
+    }
+}
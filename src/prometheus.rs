@@ -0,0 +1,87 @@
+use std::fmt::Write;
+
+use crate::{normalize_mac, Wifi};
+
+/// Formats the signal of each hotspot in the Prometheus text exposition format, e.g.
+/// for a monitoring agent to serve on its `/metrics` endpoint:
+///
+/// ```text
+/// # HELP wifi_signal_dbm Signal strength of the hotspot in dBm.
+/// # TYPE wifi_signal_dbm gauge
+/// wifi_signal_dbm{ssid="home",bssid="00:11:22:33:44:55",channel="6"} -67
+/// ```
+///
+/// Mac addresses are lowercased with `airport`'s missing leading zeros added back, so a
+/// hotspot keeps the same labels across platforms. Hotspots whose signal couldn't be
+/// parsed are left out.
+pub fn to_prometheus(wifis: &[Wifi]) -> String {
+    let mut metrics = String::from(
+        "# HELP wifi_signal_dbm Signal strength of the hotspot in dBm.\n\
+         # TYPE wifi_signal_dbm gauge\n",
+    );
+    for wifi in wifis {
+        if let Some(signal) = wifi.signal_dbm() {
+            let _ = writeln!(
+                metrics,
+                "wifi_signal_dbm{{ssid=\"{}\",bssid=\"{}\",channel=\"{}\"}} {}",
+                escape_label(&wifi.ssid),
+                escape_label(&normalize_mac(&wifi.mac)),
+                escape_label(wifi.channel.trim()),
+                signal
+            );
+        }
+    }
+    metrics
+}
+
+/// Escapes `\`, `"` and line feeds, the only characters label values can't contain.
+fn escape_label(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_escape_label_values() {
+        assert_eq!("home", escape_label("home"));
+        assert_eq!(r#"say \"hi\""#, escape_label(r#"say "hi""#));
+        assert_eq!(r"back\\slash", escape_label(r"back\slash"));
+        assert_eq!(r"two\nlines", escape_label("two\nlines"));
+    }
+
+    #[test]
+    fn should_format_signal_metrics() {
+        let wifis = vec![
+            Wifi {
+                mac: "AA:BB:CC:DD:EE:01".to_string(),
+                ssid: "cafe \"guest\"".to_string(),
+                channel: "6".to_string(),
+                signal_level: "-67.00".to_string(),
+                ..Default::default()
+            },
+            Wifi {
+                mac: "aa:bb:cc:dd:ee:02".to_string(),
+                ssid: "no signal".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(
+            "# HELP wifi_signal_dbm Signal strength of the hotspot in dBm.\n\
+             # TYPE wifi_signal_dbm gauge\n\
+             wifi_signal_dbm{ssid=\"cafe \\\"guest\\\"\",bssid=\"aa:bb:cc:dd:ee:01\",channel=\"6\"} -67\n",
+            to_prometheus(&wifis)
+        );
+    }
+}
@@ -0,0 +1,20 @@
+/// Whether the WiFi radios are turned off, see `radio_status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RadioStatus {
+    /// at least one radio can be used
+    Unblocked,
+    /// turned off in software, e.g. by airplane mode or `rfkill block wifi`, undone
+    /// with `rfkill unblock wifi`
+    SoftBlocked,
+    /// turned off by a hardware switch or key, which has to be flipped to scan
+    HardBlocked,
+    /// no WiFi radio was found
+    NoRadio,
+}
+
+impl RadioStatus {
+    /// Returns true if the radios are turned off, so scanning can't find anything.
+    pub fn is_blocked(self) -> bool {
+        self == RadioStatus::SoftBlocked || self == RadioStatus::HardBlocked
+    }
+}
@@ -0,0 +1,521 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::Wifi;
+
+/// Security used by a hotspot, normalized from the platform specific `Wifi::security`
+/// string (e.g. `WPA2(PSK/AES/AES)` on macOS, `WPA2-Personal` on Windows and `WPA2(PSK)` on
+/// Linux).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Security {
+    /// no encryption
+    Open,
+    Wep,
+    /// WPA or WPA2 with a pre-shared key
+    Personal,
+    /// WPA3 with simultaneous authentication of equals (SAE)
+    Wpa3Personal,
+    /// WPA, WPA2 or WPA3 using 802.1X, i.e. needs a username and password
+    Enterprise,
+    /// security string that isn't recognised
+    Unknown,
+}
+
+impl Security {
+    /// Normalizes a security string as reported by `airport`, `iw` or `netsh`.
+    /// An empty string is treated as `Open` as that's what `iw` reports for open networks.
+    pub fn parse(security: &str) -> Security {
+        let security = security.trim().to_lowercase();
+
+        if security.is_empty() || security == "none" || security == "open" {
+            Security::Open
+        } else if security.contains("802.1x") || security.contains("enterprise") {
+            Security::Enterprise
+        } else if security.contains("sae") || security.contains("wpa3") {
+            Security::Wpa3Personal
+        } else if security.contains("psk") || security.contains("personal") {
+            Security::Personal
+        } else if security.contains("wep") {
+            Security::Wep
+        } else {
+            Security::Unknown
+        }
+    }
+}
+
+/// Normalizes a security string as reported by `airport`, `iw`, `iwlist`, `netsh` or
+/// NetworkManager to the form `netsh` uses, so the same hotspot reports the same
+/// `Wifi::security` on every platform, e.g. `PSK` and `WPA2(PSK/AES/AES)` both become
+/// `WPA2-Personal`.
+///
+/// Networks advertising both WPA and WPA2 (mixed mode) are reported as e.g.
+/// `WPA/WPA2-Personal`. Bare suites without a version, like `PSK` and `IEEE 802.1X`, are
+/// reported as WPA2. Strings that aren't recognised are returned trimmed but otherwise
+/// unchanged.
+pub fn canonical_security(security: &str) -> String {
+    let lowercase = security.to_lowercase();
+    // `wpa` not followed by a version number, i.e. WPA1
+    let has_wpa1 = lowercase
+        .match_indices("wpa")
+        .any(|(i, _)| !lowercase[i + 3..].starts_with(|c: char| c.is_ascii_digit()));
+    let version = if lowercase.contains("wpa3") {
+        "WPA3"
+    } else if lowercase.contains("wpa2") && has_wpa1 {
+        "WPA/WPA2"
+    } else if lowercase.contains("wpa2") || !lowercase.contains("wpa") {
+        "WPA2"
+    } else {
+        "WPA"
+    };
+
+    match Security::parse(security) {
+        Security::Open => "Open".to_string(),
+        Security::Wep => "WEP".to_string(),
+        Security::Personal => format!("{}-Personal", version),
+        Security::Wpa3Personal => "WPA3-Personal".to_string(),
+        Security::Enterprise => format!("{}-Enterprise", version),
+        Security::Unknown => security.trim().to_string(),
+    }
+}
+
+/// Converts wpa_supplicant's flags like `[WPA-PSK-TKIP][WPA2-PSK+SAE-CCMP][ESS]` to the
+/// security string `iw` would report, e.g. `WPA(PSK) WPA2(PSK SAE)`, along with the RSN
+/// element's authentication suites.
+///
+/// Android reports the same flags, but repeats the RSN element as both `WPA2-` and
+/// `RSN-`.
+#[cfg_attr(not(any(target_os = "linux", feature = "android")), allow(dead_code))]
+pub(crate) fn parse_supplicant_flags(flags: &str) -> (String, Vec<String>) {
+    let mut security = Vec::new();
+    let mut akm_suites = Vec::new();
+
+    for flag in flags.split(['[', ']']) {
+        let (version, rest) = match flag.split_once('-') {
+            Some(("WPA", rest)) => ("WPA", rest),
+            Some(("WPA2", rest)) | Some(("RSN", rest)) => ("WPA2", rest),
+            _ if flag == "WEP" => {
+                security.push(flag.to_string());
+                continue;
+            }
+            _ => continue,
+        };
+        // the suites come before the ciphers, e.g. `EAP-SHA256` in `EAP-SHA256-CCMP`
+        let suites = rest
+            .split('-')
+            .take_while(|part| !["CCMP", "TKIP", "GCMP"].iter().any(|c| part.contains(c)))
+            .collect::<Vec<_>>()
+            .join("-");
+        let suites: Vec<String> = suites.split('+').map(akm_suite_name).collect();
+
+        let entry = format!("{}({})", version, suites.join(" "));
+        if !security.contains(&entry) {
+            security.push(entry);
+        }
+        if version == "WPA2" {
+            akm_suites = suites;
+        }
+    }
+
+    (security.join(" "), akm_suites)
+}
+
+/// Renames wpa_supplicant's authentication suites to the names `iw` uses.
+fn akm_suite_name(suite: &str) -> String {
+    match suite {
+        // Android spells out the hash of the plain 802.1X suites
+        "EAP" | "EAP/SHA1" => "IEEE 802.1X",
+        "FT/EAP" => "FT/IEEE 802.1X",
+        "EAP-SHA256" | "EAP/SHA256" => "IEEE 802.1X/SHA-256",
+        "PSK-SHA256" => "PSK/SHA-256",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Returns the SSIDs that look like they might be an evil twin, sorted by name.
+///
+/// An SSID is reported when either:
+///
+/// - it's advertised with more than one kind of `Security`, e.g. a WPA2 network that
+///   also appears as Open
+/// - its hotspots don't all share the same vendor, i.e. the first three octets (OUI)
+///   of their mac addresses differ
+///
+/// These are heuristics, so expect false positives: mesh networks built from
+/// different vendors' hardware, hotspots using randomized (locally administered) mac
+/// addresses and networks in the middle of a security upgrade will all be reported.
+/// An attacker cloning both the security and the vendor prefix won't be detected.
+/// Hidden networks (empty SSID) are ignored.
+pub fn suspicious_ssids(wifis: &[Wifi]) -> Vec<String> {
+    let mut securities: BTreeMap<&str, BTreeSet<Security>> = BTreeMap::new();
+    let mut vendors: BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+
+    for wifi in wifis.iter().filter(|wifi| !wifi.ssid.is_empty()) {
+        securities
+            .entry(&wifi.ssid)
+            .or_default()
+            .insert(wifi.security_type());
+        vendors
+            .entry(&wifi.ssid)
+            .or_default()
+            .insert(oui(&wifi.mac));
+    }
+
+    securities
+        .into_iter()
+        .filter(|(ssid, securities)| securities.len() > 1 || vendors[ssid].len() > 1)
+        .map(|(ssid, _)| ssid.to_string())
+        .collect()
+}
+
+/// Returns the hotspots whose SSID looks like one of the `legit` names without being
+/// the same bytes, e.g. `Нome` spelt with a Cyrillic `Н`, in the order they were found.
+///
+/// Both names are reduced to a skeleton before comparing: Cyrillic and Greek letters
+/// that look like Latin ones and fullwidth forms are folded to ASCII, `0` and `O` to
+/// `o`, `1` and `I` to `l`, and invisible characters like zero width spaces are
+/// dropped. Only these common confusables are known, so lookalikes using other scripts
+/// or accented letters aren't detected. The legit names are compared to `ssid_raw`, so
+/// an SSID that only matches once its invalid UTF-8 is replaced is still flagged.
+/// Hidden networks are ignored.
+pub fn suspicious_homoglyph_ssids(wifis: &[Wifi], legit: &[&str]) -> Vec<Wifi> {
+    let legit: Vec<(&str, String)> = legit.iter().map(|name| (*name, skeleton(name))).collect();
+
+    wifis
+        .iter()
+        .filter(|wifi| !wifi.is_hidden())
+        .filter(|wifi| {
+            let raw = if wifi.ssid_raw.is_empty() {
+                wifi.ssid.as_bytes()
+            } else {
+                &wifi.ssid_raw
+            };
+            let ssid = skeleton(&wifi.ssid);
+            legit
+                .iter()
+                .any(|(name, skeleton)| raw != name.as_bytes() && ssid == *skeleton)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Folds the characters commonly used to spoof a name to the ASCII ones they look like.
+fn skeleton(name: &str) -> String {
+    name.chars()
+        .filter(|c| {
+            !matches!(
+                c,
+                '\u{200b}'..='\u{200f}' | '\u{2060}' | '\u{feff}' | '\u{ad}'
+            )
+        })
+        .map(|c| match c {
+            // fullwidth ASCII, e.g. `Ｈｏｍｅ`
+            '\u{ff01}'..='\u{ff5e}' => char::from_u32(c as u32 - 0xfee0).unwrap_or(c),
+            // Cyrillic
+            'а' => 'a',
+            'е' => 'e',
+            'о' => 'o',
+            'р' => 'p',
+            'с' => 'c',
+            'у' => 'y',
+            'х' => 'x',
+            'і' => 'i',
+            'ј' => 'j',
+            'ѕ' => 's',
+            'ԁ' => 'd',
+            'һ' => 'h',
+            'ԛ' => 'q',
+            'ԝ' => 'w',
+            'А' => 'A',
+            'В' => 'B',
+            'Е' => 'E',
+            'К' => 'K',
+            'М' => 'M',
+            'Н' => 'H',
+            'О' => 'O',
+            'Р' => 'P',
+            'С' => 'C',
+            'Т' => 'T',
+            'Х' => 'X',
+            'Ѕ' => 'S',
+            'І' => 'I',
+            'Ј' => 'J',
+            // Greek
+            'ο' => 'o',
+            'α' => 'a',
+            'ν' => 'v',
+            'ι' => 'i',
+            'κ' => 'k',
+            'Α' => 'A',
+            'Β' => 'B',
+            'Ε' => 'E',
+            'Ζ' => 'Z',
+            'Η' => 'H',
+            'Ι' => 'I',
+            'Κ' => 'K',
+            'Μ' => 'M',
+            'Ν' => 'N',
+            'Ο' => 'O',
+            'Ρ' => 'P',
+            'Τ' => 'T',
+            'Υ' => 'Y',
+            'Χ' => 'X',
+            c => c,
+        })
+        .map(|c| match c {
+            '0' | 'O' => 'o',
+            '1' | 'I' => 'l',
+            c => c,
+        })
+        .collect()
+}
+
+/// Thresholds used by `detect_rogue_with`.
+///
+/// The defaults flag a hotspot whose signal got `20` dB stronger, or a new hotspot of
+/// a watched SSID at `-60` dBm or stronger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RogueThresholds {
+    /// smallest increase in signal since the baseline, in dB, that is flagged
+    pub signal_jump_db: i32,
+    /// weakest signal in dBm at which a new hotspot of a watched SSID is flagged
+    pub new_ap_min_dbm: i32,
+}
+
+impl Default for RogueThresholds {
+    fn default() -> RogueThresholds {
+        RogueThresholds {
+            signal_jump_db: 20,
+            new_ap_min_dbm: -60,
+        }
+    }
+}
+
+/// Same as `detect_rogue_with`, using the default `RogueThresholds`.
+pub fn detect_rogue(baseline: &[Wifi], current: &[Wifi], ssid_watchlist: &[&str]) -> Vec<Wifi> {
+    detect_rogue_with(
+        baseline,
+        current,
+        ssid_watchlist,
+        RogueThresholds::default(),
+    )
+}
+
+/// Compares a scan to a baseline scan taken earlier and returns the hotspots of
+/// `current` that might be a rogue access point, in the order they were found:
+///
+/// - hotspots seen in the baseline whose signal got at least `signal_jump_db`
+///   stronger, e.g. someone walking up with a portable access point
+/// - hotspots missing from the baseline advertising an SSID of `ssid_watchlist` with a
+///   signal of at least `new_ap_min_dbm`, e.g. an evil twin of your own network
+///
+/// Hotspots are matched on their mac address. Like `suspicious_ssids` these are
+/// heuristics: you walking around, a new access point of your own and a spoofed mac
+/// address all fool them.
+pub fn detect_rogue_with(
+    baseline: &[Wifi],
+    current: &[Wifi],
+    ssid_watchlist: &[&str],
+    thresholds: RogueThresholds,
+) -> Vec<Wifi> {
+    let baseline: HashMap<String, &Wifi> = baseline
+        .iter()
+        .map(|wifi| (crate::normalize_mac(&wifi.mac), wifi))
+        .collect();
+
+    current
+        .iter()
+        .filter(
+            |wifi| match baseline.get(&crate::normalize_mac(&wifi.mac)) {
+                Some(before) => match (before.signal_dbm(), wifi.signal_dbm()) {
+                    (Some(before), Some(now)) => now - before >= thresholds.signal_jump_db,
+                    _ => false,
+                },
+                None => {
+                    ssid_watchlist.contains(&wifi.ssid.as_str())
+                        && wifi
+                            .signal_dbm()
+                            .is_some_and(|dbm| dbm >= thresholds.new_ap_min_dbm)
+                }
+            },
+        )
+        .cloned()
+        .collect()
+}
+
+/// Returns the vendor prefix (first three octets) of a mac address.
+pub(crate) fn oui(mac: &str) -> String {
+    crate::normalize_mac(mac)
+        .split(':')
+        .take(3)
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wifi(mac: &str, ssid: &str, signal_level: &str, security: &str) -> Wifi {
+        Wifi {
+            mac: mac.to_string(),
+            ssid: ssid.to_string(),
+            signal_level: signal_level.to_string(),
+            security: security.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_parse_supplicant_flags() {
+        assert_eq!(
+            ("WEP".to_string(), vec![]),
+            parse_supplicant_flags("[WEP][ESS]")
+        );
+        assert_eq!(
+            (
+                "WPA2(IEEE 802.1X)".to_string(),
+                vec!["IEEE 802.1X".to_string()]
+            ),
+            parse_supplicant_flags("[WPA2-EAP/SHA1-CCMP][RSN-EAP/SHA1-CCMP][ESS]")
+        );
+    }
+
+    #[test]
+    fn should_parse_security() {
+        assert_eq!(Security::Open, Security::parse(""));
+        assert_eq!(Security::Open, Security::parse("NONE"));
+        assert_eq!(Security::Open, Security::parse("Open"));
+        assert_eq!(Security::Wep, Security::parse("WEP"));
+        assert_eq!(Security::Personal, Security::parse("PSK"));
+        assert_eq!(Security::Personal, Security::parse("WPA2(PSK/AES/AES)"));
+        assert_eq!(Security::Personal, Security::parse("WPA2-Personal"));
+        assert_eq!(Security::Wpa3Personal, Security::parse("SAE"));
+        assert_eq!(Security::Wpa3Personal, Security::parse("WPA3-Personal"));
+        assert_eq!(Security::Enterprise, Security::parse("IEEE 802.1X"));
+        assert_eq!(Security::Enterprise, Security::parse("WPA2-Enterprise"));
+        assert_eq!(Security::Unknown, Security::parse("foo"));
+    }
+
+    #[test]
+    fn should_canonicalize_security() {
+        // the same WPA2 network as reported by `iw`, `airport`, `netsh` and NetworkManager
+        for security in &["PSK", "WPA2(PSK/AES/AES)", "WPA2-Personal", "wpa2-psk"] {
+            assert_eq!("WPA2-Personal", canonical_security(security));
+        }
+        assert_eq!("WPA-Personal", canonical_security("WPA(PSK/TKIP/TKIP)"));
+        assert_eq!("WPA3-Personal", canonical_security("PSK SAE"));
+        assert_eq!("WPA-Personal", canonical_security("WPA(PSK)"));
+        assert_eq!(
+            "WPA/WPA2-Personal",
+            canonical_security("WPA(PSK) WPA2(PSK)")
+        );
+        assert_eq!(
+            "WPA/WPA2-Personal",
+            canonical_security("WPA(PSK/TKIP/TKIP) WPA2(PSK/AES/AES)")
+        );
+        assert_eq!("WPA3-Personal", canonical_security("WPA2(PSK SAE)"));
+        assert_eq!("WPA2-Enterprise", canonical_security("WPA2(IEEE 802.1X)"));
+        assert_eq!("WPA2-Enterprise", canonical_security("IEEE 802.1X"));
+        assert_eq!(
+            "WPA2-Enterprise",
+            canonical_security("WPA2(802.1x/AES/AES)")
+        );
+        assert_eq!("Open", canonical_security(""));
+        assert_eq!("Open", canonical_security("NONE"));
+        assert_eq!("WEP", canonical_security("WEP"));
+        assert_eq!("foo", canonical_security(" foo "));
+    }
+
+    #[test]
+    fn should_find_suspicious_ssids() {
+        let wifis = [
+            wifi("00:35:1a:90:56:03", "OurTest", "", "WPA2(PSK/AES/AES)"),
+            wifi("00:35:1a:90:56:04", "OurTest", "", "NONE"),
+            wifi("00:35:1a:90:56:05", "TEST-Wifi", "", "WPA2(PSK/AES/AES)"),
+            wifi("ab:cd:ef:01:23:45", "TEST-Wifi", "", "WPA2(PSK/AES/AES)"),
+            wifi("00:35:1a:90:56:06", "OurDev", "", "WPA2(PSK/AES/AES)"),
+            wifi("00:35:1a:90:56:07", "OurDev", "", "WPA2(PSK/AES/AES)"),
+            wifi("00:35:1a:90:56:08", "", "", "NONE"),
+            wifi("ab:cd:ef:01:23:46", "", "", "WPA2(PSK/AES/AES)"),
+        ];
+
+        assert_eq!(
+            vec!["OurTest".to_string(), "TEST-Wifi".to_string()],
+            suspicious_ssids(&wifis)
+        );
+    }
+
+    #[test]
+    fn should_detect_rogue() {
+        let baseline = [
+            wifi("00:11:22:33:44:01", "home", "-50", ""),
+            wifi("00:11:22:33:44:02", "cafe", "-80", ""),
+            wifi("00:11:22:33:44:03", "neighbour", "-75", ""),
+        ];
+        let current = [
+            wifi("00:11:22:33:44:01", "home", "-45", ""),
+            wifi("00:11:22:33:44:02", "cafe", "-55", ""),
+            wifi("00:11:22:33:44:03", "neighbour", "", ""),
+            wifi("66:77:88:99:aa:01", "home", "-40", ""),
+            wifi("66:77:88:99:aa:02", "home", "-85", ""),
+            wifi("66:77:88:99:aa:03", "other", "-30", ""),
+        ];
+
+        assert_eq!(
+            vec![current[1].clone(), current[3].clone()],
+            detect_rogue(&baseline, &current, &["home"])
+        );
+
+        let thresholds = RogueThresholds {
+            signal_jump_db: 5,
+            new_ap_min_dbm: -90,
+        };
+        assert_eq!(
+            vec![
+                current[0].clone(),
+                current[1].clone(),
+                current[3].clone(),
+                current[4].clone(),
+            ],
+            detect_rogue_with(&baseline, &current, &["home"], thresholds)
+        );
+    }
+
+    #[test]
+    fn should_find_homoglyph_ssids() {
+        let wifis = [
+            wifi("00:00:00:00:00:01", "Home", "", ""),
+            wifi("00:00:00:00:00:02", "\u{41d}ome", "", ""),
+            wifi("00:00:00:00:00:03", "H\u{43e}m\u{435}", "", ""),
+            wifi(
+                "00:00:00:00:00:04",
+                "\u{ff28}\u{ff4f}\u{ff4d}\u{ff45}",
+                "",
+                "",
+            ),
+            wifi("00:00:00:00:00:05", "Ho\u{200b}me", "", ""),
+            wifi("00:00:00:00:00:06", "H0me", "", ""),
+            wifi("00:00:00:00:00:07", "home", "", ""),
+            wifi("00:00:00:00:00:08", "Office", "", ""),
+            wifi("00:00:00:00:00:09", "0ff\u{456}ce", "", ""),
+            wifi("00:00:00:00:00:10", "", "", ""),
+        ];
+
+        assert_eq!(
+            wifis[1..6]
+                .iter()
+                .chain(&wifis[8..9])
+                .cloned()
+                .collect::<Vec<_>>(),
+            suspicious_homoglyph_ssids(&wifis, &["Home", "Office"])
+        );
+
+        // the raw bytes tell names that aren't UTF-8 apart
+        let mut invalid = wifi("00:00:00:00:00:11", "Home", "", "");
+        invalid.ssid_raw = b"Hom\xe9".to_vec();
+        assert_eq!(
+            vec![invalid.clone()],
+            suspicious_homoglyph_ssids(&[invalid], &["Home"])
+        );
+    }
+}
@@ -0,0 +1,123 @@
+/// Range of signal levels mapped to a 0 to 100% quality, used to convert between the
+/// percentages reported by `netsh` and NetworkManager and dBm.
+///
+/// Drivers calibrate their percentages differently, so the defaults of `-100` dBm (0%)
+/// and `-50` dBm (100%) can be tuned for a specific adapter with
+/// `ScanOptions::signal_bounds`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignalBounds {
+    /// signal level in dBm reported as 0%
+    pub min_dbm: i32,
+    /// signal level in dBm reported as 100%
+    pub max_dbm: i32,
+}
+
+impl Default for SignalBounds {
+    fn default() -> SignalBounds {
+        SignalBounds {
+            min_dbm: -100,
+            max_dbm: -50,
+        }
+    }
+}
+
+impl SignalBounds {
+    /// Converts a signal level in dBm to a quality percentage, clamped to 0 to 100%.
+    pub fn to_percent(&self, dbm: i32) -> u8 {
+        if dbm >= self.max_dbm {
+            100
+        } else if dbm <= self.min_dbm {
+            0
+        } else {
+            ((dbm - self.min_dbm) * 100 / (self.max_dbm - self.min_dbm)) as u8
+        }
+    }
+
+    /// Converts a quality percentage to a signal level in dBm.
+    pub fn to_dbm(&self, percent: u8) -> i32 {
+        self.min_dbm + i32::from(percent) * (self.max_dbm - self.min_dbm) / 100
+    }
+}
+
+/// Signal strength as shown to users, see `Wifi::signal_label`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SignalLabel {
+    /// the signal couldn't be parsed
+    Unknown,
+    Weak,
+    Fair,
+    Good,
+    Excellent,
+}
+
+/// Lowest signal levels in dBm for each `SignalLabel`, anything below `fair_dbm` is
+/// `Weak`.
+///
+/// The defaults are `-50` dBm and up for `Excellent`, `-60` for `Good` and `-70` for
+/// `Fair`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignalThresholds {
+    pub excellent_dbm: i32,
+    pub good_dbm: i32,
+    pub fair_dbm: i32,
+}
+
+impl Default for SignalThresholds {
+    fn default() -> SignalThresholds {
+        SignalThresholds {
+            excellent_dbm: -50,
+            good_dbm: -60,
+            fair_dbm: -70,
+        }
+    }
+}
+
+impl SignalThresholds {
+    /// Labels a signal level in dBm.
+    pub fn label(&self, dbm: i32) -> SignalLabel {
+        if dbm >= self.excellent_dbm {
+            SignalLabel::Excellent
+        } else if dbm >= self.good_dbm {
+            SignalLabel::Good
+        } else if dbm >= self.fair_dbm {
+            SignalLabel::Fair
+        } else {
+            SignalLabel::Weak
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_with_default_bounds() {
+        let bounds = SignalBounds::default();
+        assert_eq!(-67, bounds.to_dbm(66));
+        assert_eq!(-92, bounds.to_dbm(17));
+        assert_eq!(66, bounds.to_percent(-67));
+        assert_eq!(100, bounds.to_percent(-40));
+        assert_eq!(0, bounds.to_percent(-110));
+    }
+
+    #[test]
+    fn should_convert_with_custom_bounds() {
+        let bounds = SignalBounds {
+            min_dbm: -90,
+            max_dbm: -30,
+        };
+        assert_eq!(-60, bounds.to_dbm(50));
+        assert_eq!(50, bounds.to_percent(-60));
+    }
+
+    #[test]
+    fn should_label_signal() {
+        let thresholds = SignalThresholds::default();
+        assert_eq!(SignalLabel::Excellent, thresholds.label(-40));
+        assert_eq!(SignalLabel::Excellent, thresholds.label(-50));
+        assert_eq!(SignalLabel::Good, thresholds.label(-55));
+        assert_eq!(SignalLabel::Fair, thresholds.label(-70));
+        assert_eq!(SignalLabel::Weak, thresholds.label(-71));
+    }
+}
@@ -0,0 +1,82 @@
+use std::future::Future;
+use std::panic;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+
+use futures_core::Stream;
+use tokio::task::{self, JoinHandle};
+use tokio::time::{self, Interval, MissedTickBehavior};
+
+use crate::{Result, Wifi};
+
+/// Returns a stream scanning every `interval`, yielding the result of each scan, for
+/// use with tokio.
+///
+/// The first scan starts straight away. Scans run on tokio's blocking thread pool, so
+/// this must be polled within a tokio runtime. A scan taking longer than `interval`
+/// delays the next one rather than causing a burst of scans to catch up.
+///
+/// Scanning stops when the stream is dropped, although a scan already in progress
+/// runs to completion in the background.
+pub fn scan_stream_async(interval: Duration) -> impl Stream<Item = Result<Vec<Wifi>>> {
+    ScanStream {
+        period: interval,
+        interval: None,
+        scan: None,
+    }
+}
+
+struct ScanStream {
+    period: Duration,
+    // created on the first poll, as creating it needs a runtime
+    interval: Option<Interval>,
+    scan: Option<JoinHandle<Result<Vec<Wifi>>>>,
+}
+
+impl Stream for ScanStream {
+    type Item = Result<Vec<Wifi>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        if self.scan.is_none() {
+            let period = self.period;
+            let interval = self.interval.get_or_insert_with(|| {
+                let mut interval = time::interval(period);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                interval
+            });
+            ready!(interval.poll_tick(cx));
+            self.scan = Some(task::spawn_blocking(crate::scan));
+        }
+
+        let joined = ready!(Pin::new(self.scan.as_mut().unwrap()).poll(cx));
+        self.scan = None;
+        match joined {
+            Ok(result) => Poll::Ready(Some(result)),
+            Err(err) if err.is_panic() => panic::resume_unwind(err.into_panic()),
+            // the runtime is shutting down
+            Err(_) => Poll::Ready(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future;
+
+    #[test]
+    fn should_yield_a_snapshot_per_interval() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let mut stream = Box::pin(scan_stream_async(Duration::from_millis(10)));
+
+        for _ in 0..2 {
+            // scanning fails without WiFi hardware, either way a snapshot is yielded
+            let snapshot = runtime.block_on(future::poll_fn(|cx| stream.as_mut().poll_next(cx)));
+            assert!(snapshot.is_some());
+        }
+    }
+}
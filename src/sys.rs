@@ -15,3 +15,134 @@ pub(crate) use self::windows::*;
 
 #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
 compile_error!("wifiscan doesn't compile for this platform yet");
+
+use std::borrow::Cow;
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::str;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+/// Same as `scan`, the platform's results are all parsed up front.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn scan_iter(options: &crate::ScanOptions) -> Result<std::vec::IntoIter<crate::Wifi>> {
+    scan(options, &AtomicBool::new(false)).map(|result| result.wifis.into_iter())
+}
+
+/// Runs `scan`, the regulatory domain is only set on Linux.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn with_reg_domain<T, F>(_options: &crate::ScanOptions, scan: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T>,
+{
+    scan()
+}
+
+/// Runs a command to completion like `Command::output`, but polls `cancel` while it
+/// runs and kills the command once it's set, returning `Error::Cancelled`.
+pub(crate) fn output(command: &mut Command, cancel: &AtomicBool) -> Result<Output> {
+    if cancel.load(Ordering::Relaxed) {
+        return Err(Error::Cancelled);
+    }
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|_| Error::CommandNotFound)?;
+
+    // drain the pipes on other threads, so a chatty command can't block on a full pipe
+    // while we are polling it
+    let stdout = read_to_end(child.stdout.take());
+    let stderr = read_to_end(child.stderr.take());
+
+    let status = loop {
+        if cancel.load(Ordering::Relaxed) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(Error::Cancelled);
+        }
+        // io errors are mapped like `Command::output` failures elsewhere in the crate
+        match child.try_wait().map_err(|_| Error::CommandNotFound)? {
+            Some(status) => break status,
+            None => thread::sleep(Duration::from_millis(50)),
+        }
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// Describes a command as you'd type it in a shell, without quoting, e.g. `iw dev`.
+pub(crate) fn describe(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decodes a command's output, replacing invalid UTF-8 unless `strict` is set, in which
+/// case it fails with `Error::InvalidUtf8`.
+pub(crate) fn decode(output: &[u8], strict: bool) -> Result<Cow<'_, str>> {
+    if strict {
+        str::from_utf8(output)
+            .map(Cow::Borrowed)
+            .map_err(|_| Error::InvalidUtf8)
+    } else {
+        Ok(String::from_utf8_lossy(output))
+    }
+}
+
+fn read_to_end<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn should_capture_output() {
+        let cancel = AtomicBool::new(false);
+        let output = output(Command::new("echo").arg("hello"), &cancel).unwrap();
+        assert!(output.status.success());
+        assert_eq!(b"hello\n".to_vec(), output.stdout);
+    }
+
+    #[test]
+    fn should_decode_output() {
+        assert_eq!("caf\u{fffd}", decode(b"caf\xe9", false).unwrap());
+        assert_eq!(Err(Error::InvalidUtf8), decode(b"caf\xe9", true));
+        assert_eq!("café", decode("café".as_bytes(), true).unwrap());
+    }
+
+    #[test]
+    fn should_kill_cancelled_command() {
+        let cancel = std::sync::Arc::new(AtomicBool::new(false));
+        let flag = cancel.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            flag.store(true, Ordering::Relaxed);
+        });
+
+        let start = Instant::now();
+        let result = output(Command::new("sleep").arg("10"), &cancel);
+        assert_eq!(Some(Error::Cancelled), result.err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
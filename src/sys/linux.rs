@@ -1,28 +1,396 @@
-use crate::{Error, Result, Wifi};
+use crate::backend::probe;
+use crate::channel::channel_from_frequency;
+use crate::link::parse_bitrate;
+use crate::security::canonical_security;
+use crate::{
+    BackendStatus, Error, FieldSet, Interface, LinkInfo, Result, ScanOptions, ScanReport,
+    ScanResult, Wifi,
+};
 use std::env;
+use std::fs;
 use std::process::Command;
+use std::sync::atomic::AtomicBool;
 
-/// Returns a list of WiFi hotspots in your area - (Linux) uses `iw`
-pub(crate) fn scan() -> Result<Vec<Wifi>> {
-    const PATH_ENV: &'static str = "PATH";
-    let path_system = "/usr/sbin:/sbin";
-    let path = env::var_os(PATH_ENV).map_or(path_system.to_string(), |v| {
-        format!("{}:{}", v.to_string_lossy().into_owned(), path_system)
-    });
+#[cfg(feature = "dbus")]
+mod networkmanager;
+#[cfg(feature = "netlink")]
+mod nl80211;
+mod nmcli;
+mod rfkill;
+mod wpa_cli;
+
+pub(crate) use self::nmcli::known_networks;
+pub(crate) use self::rfkill::radio_status;
+
+/// Returns a list of WiFi hotspots in your area - (Linux) uses `iw`, falling back to
+/// `iwlist` when `iw` isn't installed, then to `wpa_cli`, for systems only running
+/// wpa_supplicant, and then to `nmcli`.
+///
+/// With the `dbus` feature NetworkManager is asked first, and with the `netlink`
+/// feature nl80211 is used before shelling out, each falling back to the next when
+/// unavailable.
+///
+/// Only the `iw`, `iwlist`, `wpa_cli` and `nmcli` commands are killed when `cancel` is
+/// set, the other backends are left to finish and their results are dropped.
+///
+/// NetworkManager's list is never counted as fresh as it scans on its own schedule.
+pub(crate) fn scan(options: &ScanOptions, cancel: &AtomicBool) -> Result<ScanResult> {
+    scan_report(options, cancel).map(ScanReport::into_result)
+}
+
+/// Same as `scan`, warning when `iw`'s output has hotspots but none could be parsed.
+pub(crate) fn scan_report(options: &ScanOptions, cancel: &AtomicBool) -> Result<ScanReport> {
+    #[cfg(feature = "dbus")]
+    {
+        if let Ok(wifis) = networkmanager::scan(options.signal_bounds) {
+            return cancelled_or(ScanResult::cached(wifis), cancel).map(ScanReport::from);
+        }
+    }
+    #[cfg(feature = "netlink")]
+    {
+        if let Ok((wifis, was_fresh)) = nl80211::scan(&options.scan_frequencies()) {
+            return cancelled_or(ScanResult { wifis, was_fresh }, cancel).map(ScanReport::from);
+        }
+    }
+    scan_cli(options, cancel)
+}
+
+/// Returns the hotspots found by the last scan without scanning again - (Linux) uses
+/// `iw dev <interface> scan dump`. `iwlist` can't do this, so when `iw` isn't
+/// installed a fresh scan is done, and without either `wpa_cli scan_results` is used.
+///
+/// With the `dbus` feature NetworkManager's list is returned, which it keeps up to date
+/// itself, and with the `netlink` feature the kernel's results are read over nl80211.
+pub(crate) fn scan_cached(options: &ScanOptions) -> Result<ScanResult> {
+    #[cfg(feature = "dbus")]
+    {
+        if let Ok(wifis) = networkmanager::scan(options.signal_bounds) {
+            return Ok(ScanResult::cached(wifis));
+        }
+    }
+    #[cfg(feature = "netlink")]
+    {
+        if let Ok(wifis) = nl80211::scan_cached() {
+            return Ok(ScanResult::cached(wifis));
+        }
+    }
+    let cancel = AtomicBool::new(false);
+    match scan_iw_cached(options) {
+        Err(Error::CommandNotFound) => {}
+        result => return result.map(ScanResult::cached),
+    }
+    match scan_iwlist(options, &cancel) {
+        Err(Error::CommandNotFound) => {
+            wpa_cli::scan_cached(options, &cancel).map(ScanResult::cached)
+        }
+        result => result.map(ScanResult::fresh),
+    }
+}
 
-    let output = Command::new("iw")
-        .env(PATH_ENV, path.clone())
+fn scan_iw_cached(options: &ScanOptions) -> Result<Vec<Wifi>> {
+    let interface = iw_interface()?;
+
+    let output = sbin_command("iw")
         .arg("dev")
+        .arg(interface)
+        .arg("scan")
+        .arg("dump")
         .output()
         .map_err(|_| Error::CommandNotFound)?;
-    let data = String::from_utf8_lossy(&output.stdout);
-    let interface = parse_iw_dev(&data)?;
+    if !output.status.success() {
+        return Err(Error::CommandFailed(
+            output.status,
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    let data = super::decode(&output.stdout, options.strict_utf8)?;
+    parse_iw_dev_scan(&data, options.parsed_fields())
+}
+
+#[cfg(any(feature = "dbus", feature = "netlink"))]
+fn cancelled_or(result: ScanResult, cancel: &AtomicBool) -> Result<ScanResult> {
+    if cancel.load(std::sync::atomic::Ordering::Relaxed) {
+        Err(Error::Cancelled)
+    } else {
+        Ok(result)
+    }
+}
+
+fn scan_cli(options: &ScanOptions, cancel: &AtomicBool) -> Result<ScanReport> {
+    let mut report = match scan_iw(options, cancel) {
+        Err(Error::CommandNotFound) => scan_without_iw(options, cancel),
+        result => result,
+    }?;
+    if options.merge_nmcli {
+        match nmcli::scan_report(options, cancel) {
+            Err(Error::Cancelled) => return Err(Error::Cancelled),
+            Ok(nmcli) => {
+                report.wifis = merge_by_bssid(report.wifis, nmcli.wifis);
+                report.warnings.extend(nmcli.warnings);
+            }
+            Err(_) => {}
+        }
+    }
+    Ok(report)
+}
+
+/// Fills in the fields of `wifis` left empty from the hotspots of `others` with the
+/// same mac address, appending those of `others` that `wifis` doesn't have.
+fn merge_by_bssid(mut wifis: Vec<Wifi>, others: Vec<Wifi>) -> Vec<Wifi> {
+    for other in others {
+        let mac = crate::normalize_mac(&other.mac);
+        match wifis
+            .iter_mut()
+            .find(|wifi| crate::normalize_mac(&wifi.mac) == mac)
+        {
+            Some(wifi) => wifi.fill_missing(other),
+            None => wifis.push(other),
+        }
+    }
+    wifis
+}
+
+fn scan_without_iw(options: &ScanOptions, cancel: &AtomicBool) -> Result<ScanReport> {
+    match scan_iwlist(options, cancel) {
+        Err(Error::CommandNotFound) => match wpa_cli::scan_report(options, cancel) {
+            Err(Error::CommandNotFound) => nmcli::scan_report(options, cancel),
+            result => result,
+        },
+        result => result.map(|wifis| ScanResult::fresh(wifis).into()),
+    }
+}
+
+/// Same as `scan`, but `iw`'s output is parsed one hotspot at a time as the iterator
+/// is advanced. The other backends' results are parsed up front.
+pub(crate) fn scan_iter(options: &ScanOptions) -> Result<ScanIter> {
+    let cancel = AtomicBool::new(false);
+    #[cfg(feature = "dbus")]
+    {
+        if let Ok(wifis) = networkmanager::scan(options.signal_bounds) {
+            return Ok(ScanIter::Parsed(wifis.into_iter()));
+        }
+    }
+    #[cfg(feature = "netlink")]
+    {
+        if let Ok((wifis, _)) = nl80211::scan(&options.scan_frequencies()) {
+            return Ok(ScanIter::Parsed(wifis.into_iter()));
+        }
+    }
+    match scan_iw_output(options, &cancel) {
+        Ok(output) => Ok(ScanIter::Iw {
+            output,
+            position: 0,
+            fields: options.parsed_fields(),
+        }),
+        Err(Error::CommandNotFound) => scan_without_iw(options, &cancel)
+            .map(|report| ScanIter::Parsed(report.wifis.into_iter())),
+        Err(err) => Err(err),
+    }
+}
+
+/// Hotspots returned by `scan_iter`.
+pub(crate) enum ScanIter {
+    /// `iw`'s output, parsed from `position` on
+    Iw {
+        output: String,
+        position: usize,
+        fields: FieldSet,
+    },
+    Parsed(std::vec::IntoIter<Wifi>),
+}
+
+impl Iterator for ScanIter {
+    type Item = Wifi;
+
+    fn next(&mut self) -> Option<Wifi> {
+        match self {
+            ScanIter::Iw {
+                output,
+                position,
+                fields,
+            } => {
+                while *position < output.len() {
+                    // each hotspot starts with a `BSS <mac>(on <interface>)` line
+                    let end = output[*position..]
+                        .find("\nBSS ")
+                        .map_or(output.len(), |end| *position + end + 1);
+                    let block = &output[*position..end];
+                    *position = end;
+                    // incomplete hotspots are skipped, like `parse_iw_dev_scan` does
+                    if let Some(wifi) = parse_iw_dev_scan(block, *fields)
+                        .ok()
+                        .and_then(|wifis| wifis.into_iter().next())
+                    {
+                        return Some(wifi);
+                    }
+                }
+                None
+            }
+            ScanIter::Parsed(wifis) => wifis.next(),
+        }
+    }
+}
+
+fn scan_iw(options: &ScanOptions, cancel: &AtomicBool) -> Result<ScanReport> {
+    let output = scan_iw_output(options, cancel)?;
+    let wifis = parse_iw_dev_scan(&output, options.parsed_fields())?;
+    Ok(ScanReport::checked(
+        ScanResult::fresh(wifis),
+        &output,
+        is_iw_hotspot_line,
+    ))
+}
+
+fn is_iw_hotspot_line(line: &str) -> bool {
+    line.starts_with("BSS ")
+}
+
+fn scan_iw_output(options: &ScanOptions, cancel: &AtomicBool) -> Result<String> {
+    let interface = iw_interface()?;
+
+    let frequencies = options.scan_frequencies();
+    let mut output = super::output(&mut iw_scan_command(&interface, &frequencies), cancel)?;
+    // the driver may not support every channel of the band, see `ScanOptions::band`
+    if !output.status.success() && options.band.is_some() && options.frequencies.is_empty() {
+        output = super::output(&mut iw_scan_command(&interface, &[]), cancel)?;
+    }
+    if !output.status.success() {
+        return Err(Error::CommandFailed(
+            output.status,
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    Ok(super::decode(&output.stdout, options.strict_utf8)?.into_owned())
+}
+
+/// Runs `scan` after setting the regulatory domain of `ScanOptions::set_reg_domain`
+/// with `iw reg set`, restoring the previous one afterwards if asked to.
+pub(crate) fn with_reg_domain<T, F>(options: &ScanOptions, scan: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T>,
+{
+    with_reg_domain_using(options, iw_reg, scan)
+}
+
+fn with_reg_domain_using<T, R, F>(options: &ScanOptions, mut iw_reg: R, scan: F) -> Result<T>
+where
+    R: FnMut(&[&str]) -> Result<String>,
+    F: FnOnce() -> Result<T>,
+{
+    let country = match &options.reg_domain {
+        Some(country) => country,
+        None => return scan(),
+    };
+    // fail before changing anything rather than leave the domain changed for good
+    let previous = if options.restore_reg_domain {
+        Some(parse_iw_reg_get(&iw_reg(&["get"])?).ok_or(Error::FailedToParse)?)
+    } else {
+        None
+    };
+
+    iw_reg(&["set", country])?;
+    let result = scan();
+    match previous {
+        // restore even after a failed scan, whose error is more useful than the restore's
+        Some(previous) => {
+            let restored = iw_reg(&["set", &previous]);
+            result.and_then(|value| restored.map(|_| value))
+        }
+        None => result,
+    }
+}
+
+fn iw_reg(args: &[&str]) -> Result<String> {
+    let output = super::output(
+        sbin_command("iw").arg("reg").args(args),
+        &AtomicBool::new(false),
+    )?;
+    // e.g. not allowed to change the domain without root
+    if !output.status.success() {
+        return Err(Error::CommandFailed(
+            output.status,
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Returns the country code of the `global` domain `iw reg get` lists first, ahead of
+/// any domains of self-managed wiphys.
+fn parse_iw_reg_get(reg: &str) -> Option<String> {
+    reg.lines()
+        .find_map(|line| line.strip_prefix("country "))
+        .and_then(|line| line.split(':').next())
+        .map(str::to_string)
+}
+
+fn iw_scan_command(interface: &str, frequencies: &[u32]) -> Command {
+    let mut command = sbin_command("iw");
+    command.arg("dev").arg(interface).arg("scan");
+    if !frequencies.is_empty() {
+        command.arg("freq");
+        command.args(frequencies.iter().map(u32::to_string));
+    }
+    command
+}
+
+/// Lists the commands `scan` runs, in the order they're tried - (Linux) `iw`,
+/// `iwlist`, `wpa_cli` then `nmcli`. `<interface>` stands for the interface `iw dev` finds
+pub(crate) fn scan_commands(options: &ScanOptions) -> Vec<String> {
+    vec![
+        super::describe(sbin_command("iw").arg("dev")),
+        super::describe(&iw_scan_command("<interface>", &options.scan_frequencies())),
+        super::describe(sbin_command("iwlist").arg("scanning")),
+        super::describe(sbin_command("wpa_cli").arg("scan")),
+        super::describe(sbin_command("wpa_cli").arg("scan_results")),
+        super::describe(&nmcli::scan_command(options.fields)),
+    ]
+}
+
+/// Scans every interface as `iwlist` finds them itself, unlike `iw` which needs to be
+/// told which interface to use.
+fn scan_iwlist(options: &ScanOptions, cancel: &AtomicBool) -> Result<Vec<Wifi>> {
+    let output = super::output(sbin_command("iwlist").arg("scanning"), cancel)?;
+    if !output.status.success() {
+        return Err(Error::CommandFailed(
+            output.status,
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    let data = super::decode(&output.stdout, options.strict_utf8)?;
+    parse_iwlist_scanning(&data)
+}
+
+/// Returns the hotspot you are currently connected to - (Linux) uses `iw`
+pub(crate) fn connected_network() -> Result<Option<Wifi>> {
+    parse_iw_dev_link(&iw_dev_link()?)
+}
+
+/// Returns the current connection's details - (Linux) uses `iw`
+pub(crate) fn connected_link_info() -> Result<Option<LinkInfo>> {
+    parse_iw_dev_link_info(&iw_dev_link()?)
+}
+
+/// Probes the tools used to scan - (Linux) `iw`, falling back to `iwlist`, `wpa_cli`
+/// then `nmcli`
+pub(crate) fn check_backend() -> Result<BackendStatus> {
+    Ok(BackendStatus {
+        tools: vec![
+            probe("iw", sbin_command("iw").arg("--version")),
+            probe("iwlist", sbin_command("iwlist").arg("--version")),
+            probe("wpa_cli", sbin_command("wpa_cli").arg("-v")),
+            probe("nmcli", Command::new("nmcli").arg("--version")),
+        ],
+    })
+}
+
+fn iw_dev_link() -> Result<String> {
+    let interface = iw_interface()?;
 
-    let output = Command::new("iw")
-        .env(PATH_ENV, path)
+    let output = sbin_command("iw")
         .arg("dev")
         .arg(interface)
-        .arg("scan")
+        .arg("link")
         .output()
         .map_err(|_| Error::CommandNotFound)?;
     if !output.status.success() {
@@ -31,63 +399,427 @@ pub(crate) fn scan() -> Result<Vec<Wifi>> {
             String::from_utf8_lossy(&output.stderr).to_string(),
         ));
     }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Builds a command, adding the sbin directories to `PATH` as `iw` and `iwlist` are
+/// usually installed there.
+fn sbin_command(program: &str) -> Command {
+    const PATH_ENV: &str = "PATH";
+    let path_system = "/usr/sbin:/sbin";
+    let path = env::var_os(PATH_ENV).map_or(path_system.to_string(), |v| {
+        format!("{}:{}", v.to_string_lossy().into_owned(), path_system)
+    });
+
+    let mut command = Command::new(program);
+    command.env(PATH_ENV, path);
+    command
+}
+
+/// Returns true if there's an interface that can scan - (Linux) uses `iw`, falling back
+/// to looking for wireless interfaces in `/sys/class/net` when it isn't installed
+pub(crate) fn has_wifi_hardware() -> bool {
+    match iw_interface() {
+        Ok(_) => true,
+        Err(Error::CommandNotFound) => fs::read_dir("/sys/class/net").is_ok_and(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .any(|entry| entry.path().join("wireless").exists())
+        }),
+        Err(_) => false,
+    }
+}
+
+/// Lists the WiFi interfaces - (Linux) uses `iw dev`
+pub(crate) fn show_interfaces() -> Result<Vec<Interface>> {
+    let output = sbin_command("iw")
+        .arg("dev")
+        .output()
+        .map_err(|_| Error::CommandNotFound)?;
+    Ok(parse_iw_dev_interfaces(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// `iw dev` only prints the SSID of connected interfaces, not the BSSID.
+fn parse_iw_dev_interfaces(interfaces: &str) -> Vec<Interface> {
+    interfaces
+        .split("\tInterface ")
+        .skip(1)
+        .map(|interface| {
+            let mut lines = interface.lines();
+            let name = lines.next().unwrap_or_default().trim().to_string();
+            let ssid = lines
+                .filter_map(|line| line.trim_start().strip_prefix("ssid "))
+                .map(|ssid| {
+                    let mut wifi = Wifi::default();
+                    set_ssid(&mut wifi, ssid);
+                    wifi.ssid
+                })
+                .next();
+            Interface {
+                name,
+                connected: ssid.is_some(),
+                ssid,
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+fn iw_interface() -> Result<String> {
+    let output = sbin_command("iw")
+        .arg("dev")
+        .output()
+        .map_err(|_| Error::CommandNotFound)?;
     let data = String::from_utf8_lossy(&output.stdout);
-    parse_iw_dev_scan(&data)
+    parse_iw_dev(&data)
 }
 
+/// Returns the first interface that can scan, skipping interfaces in monitor mode.
 fn parse_iw_dev(interfaces: &str) -> Result<String> {
     interfaces
         .split("\tInterface ")
-        .take(2)
-        .last()
-        .ok_or(Error::NoValue)?
-        .split("\n")
-        .nth(0)
-        .ok_or(Error::NoValue)
-        .map(|text| text.to_string())
+        .skip(1)
+        .find(|interface| !interface.lines().any(|line| line.trim() == "type monitor"))
+        .and_then(|interface| interface.lines().next())
+        .map(|name| name.trim().to_string())
+        .ok_or(Error::NoWirelessInterface)
 }
 
-fn parse_iw_dev_scan(network_list: &str) -> Result<Vec<Wifi>> {
+/// Parses `iw dev <interface> scan` output, skipping the information elements of the
+/// optional fields not in `fields`.
+fn parse_iw_dev_scan(network_list: &str, fields: FieldSet) -> Result<Vec<Wifi>> {
+    let capabilities = fields.contains(FieldSet::CAPABILITIES);
+    let bss_load = fields.contains(FieldSet::BSS_LOAD);
+    let vendor_ies = fields.contains(FieldSet::VENDOR_IES);
+
     let mut wifis: Vec<Wifi> = Vec::new();
     let mut wifi = Wifi::default();
+    // the information element the following authentication suites belong to
+    let mut ie = "WPA2";
+    // tells WEP from open networks, even when the capabilities aren't kept
+    let mut privacy = false;
     for line in network_list.split("\n") {
         if let Ok(mac) = extract_value(line, "BSS ", Some("(")) {
-            if !wifi.mac.is_empty()
-                && !wifi.signal_level.is_empty()
-                && !wifi.channel.is_empty()
-                && !wifi.ssid.is_empty()
-            {
-                wifis.push(wifi);
-                wifi = Wifi::default();
-            }
+            push_iw_wifi(&mut wifis, wifi, privacy);
+            wifi = Wifi::default();
+            ie = "WPA2";
+            privacy = false;
             wifi.mac = mac;
         } else if let Ok(signal) = extract_value(line, "\tsignal: ", Some(" dBm")) {
             wifi.signal_level = signal;
+        } else if let Ok(noise) = extract_value(line, "\tnoise: ", Some(" dBm")) {
+            // `iw` prints the noise after the signal
+            wifi.snr_db = snr_db(wifi.signal_dbm(), &noise);
+        } else if let Ok(frequency) = extract_value(line, "\tfreq: ", None) {
+            wifi.frequency = parse_frequency(&frequency);
+        } else if let Ok(capability) = extract_value(line, "\tcapability: ", None) {
+            // e.g. `ESS Privacy ShortSlotTime (0x0411)`
+            privacy = capability.split_whitespace().any(|flag| flag == "Privacy");
+            if capabilities {
+                wifi.capabilities = capability
+                    .split_whitespace()
+                    .filter(|flag| !flag.starts_with("(0x"))
+                    .map(str::to_string)
+                    .collect();
+            }
+        } else if let Ok(last_seen) = extract_value(line, "\tlast seen: ", Some(" ms ago")) {
+            wifi.last_seen_ms_ago = last_seen.trim().parse().ok();
         } else if let Ok(channel) = extract_value(line, "\t\t * primary channel: ", None) {
             wifi.channel = channel;
+        } else if let Ok(offset) = extract_value(line, "\t\t * secondary channel offset: ", None) {
+            wifi.secondary_channel_offset = match offset.trim() {
+                "above" => Some(1),
+                "below" => Some(-1),
+                "no secondary" => Some(0),
+                _ => None,
+            };
+        } else if let Ok(color) = extract_value(line, "\t\tBSS Color: ", None) {
+            // from the HE operation element
+            wifi.bss_color = color.trim().parse().ok();
+        } else if line.starts_with("\t\t\tBSS Color Disabled") {
+            wifi.bss_color = None;
+        } else if let Ok(channel) = extract_value(line, "\tDS Parameter set: channel ", None) {
+            // legacy (802.11b/g) hotspots don't send an HT operation element
+            if wifi.channel.is_empty() {
+                wifi.channel = channel;
+            }
         } else if let Ok(ssid) = extract_value(line, "\tSSID: ", None) {
-            wifi.ssid = ssid;
-        } else if let Ok(security) = extract_value(line, "\t\t * Authentication suites: ", None) {
-            wifi.security = security;
+            // `iw` escapes spaces at either end of an SSID as `\x20`, so literal trailing
+            // whitespace, which some drivers add, isn't part of it. Spaces inside the
+            // SSID and escaped ones are kept.
+            set_ssid(&mut wifi, ssid.trim_end());
+        } else if let Some((_, indexes)) = line.split_once("MCS rate indexes supported: ") {
+            // e.g. `HT RX MCS rate indexes supported: 0-15, 32`
+            wifi.spatial_streams = wifi.spatial_streams.max(ht_spatial_streams(indexes));
+        } else if let Some((streams, _)) = line.trim_start().split_once(" streams: MCS ") {
+            // the VHT and HE MCS sets list each supported number of streams, e.g.
+            // `2 streams: MCS 0-9`, unsupported ones are `3 streams: not supported`
+            wifi.spatial_streams = wifi.spatial_streams.max(streams.parse().ok());
+        } else if let Some(count) = line
+            .strip_prefix("\t\t * station count: ")
+            .filter(|_| bss_load)
+        {
+            wifi.station_count = count.trim().parse().ok();
+        } else if let Some(utilization) = line
+            .strip_prefix("\t\t * channel utilisation: ")
+            .filter(|_| bss_load)
+        {
+            wifi.channel_utilization = utilization
+                .split_once("/255")
+                .and_then(|(utilization, _)| utilization.trim().parse().ok());
+        } else if let Some(ie) = line
+            .strip_prefix("\tVendor specific: OUI ")
+            .filter(|_| vendor_ies)
+        {
+            // e.g. `00:10:18, data: 02 00 00 1c 00 00` becomes `0010180200001c0000`
+            wifi.vendor_ies.push(
+                ie.replace(", data:", "")
+                    .split([':', ' '])
+                    .collect::<String>()
+                    .to_lowercase(),
+            );
+        } else if extract_value(line, "\tWMM:", None).is_ok() {
+            wifi.qos_enabled = true;
+        } else if line.starts_with("\tWPA:") {
+            ie = "WPA";
+        } else if line.starts_with("\tRSN:") {
+            ie = "WPA2";
+        } else if let Ok(suites) = extract_value(line, "\t\t * Authentication suites: ", None) {
+            // mixed mode networks have both a WPA and an RSN element, e.g. `WPA(PSK) WPA2(PSK)`
+            if !wifi.security.is_empty() {
+                wifi.security.push(' ');
+            }
+            wifi.security += &format!("{}({})", ie, suites);
+            if ie == "WPA2" {
+                wifi.akm_suites = split_akm_suites(&suites);
+            }
         }
     }
     // push the last wifi
-    if !wifi.mac.is_empty()
-        && !wifi.signal_level.is_empty()
-        && !wifi.channel.is_empty()
-        && !wifi.ssid.is_empty()
+    push_iw_wifi(&mut wifis, wifi, privacy);
+
+    Ok(wifis)
+}
+
+/// Splits `iw`'s space separated authentication suites, keeping names with a space
+/// like `IEEE 802.1X` and `FT/IEEE 802.1X` together.
+fn split_akm_suites(suites: &str) -> Vec<String> {
+    let mut split: Vec<String> = Vec::new();
+    for token in suites.split_whitespace() {
+        match split.last_mut() {
+            Some(last) if last.ends_with("IEEE") => {
+                last.push(' ');
+                last.push_str(token);
+            }
+            _ => split.push(token.to_string()),
+        }
+    }
+    split
+}
+
+fn push_iw_wifi(wifis: &mut Vec<Wifi>, mut wifi: Wifi, privacy: bool) {
+    if wifi.mac.is_empty()
+        || wifi.signal_level.is_empty()
+        || wifi.channel.is_empty()
+        || wifi.ssid.is_empty()
     {
-        wifis.push(wifi);
+        return;
+    }
+    // encrypted without any WPA/RSN authentication suites, otherwise open networks
+    // have no authentication suites, which becomes `Open`
+    if wifi.security.is_empty() && privacy {
+        wifi.security = "WEP".to_string();
+    }
+    wifi.security = canonical_security(&wifi.security);
+    wifis.push(wifi);
+}
+
+fn parse_iwlist_scanning(network_list: &str) -> Result<Vec<Wifi>> {
+    let mut wifis: Vec<Wifi> = Vec::new();
+    let mut wifi = Wifi::default();
+    let mut encrypted = false;
+    // a cell with a line that can't be parsed is skipped, rather than the whole scan
+    let mut malformed = false;
+    let mut ie = "";
+    for line in network_list.lines().map(str::trim_start) {
+        if let Ok(mac) = extract_value(line, "Cell ", None) {
+            if !malformed {
+                push_iwlist_wifi(&mut wifis, wifi, encrypted);
+            }
+            wifi = Wifi::default();
+            encrypted = false;
+            ie = "";
+            match mac.split("Address: ").nth(1) {
+                Some(mac) => {
+                    wifi.mac = mac.trim().to_string();
+                    malformed = false;
+                }
+                None => malformed = true,
+            }
+        } else if let Ok(channel) = extract_value(line, "Channel:", None) {
+            wifi.channel = channel;
+        } else if let Ok(frequency) = extract_value(line, "Frequency:", Some(" GHz")) {
+            wifi.frequency = frequency
+                .parse::<f64>()
+                .ok()
+                .map(|ghz| (ghz * 1000.0).round() as u32);
+        } else if let Ok(signal) = extract_value(line, "Quality=", Some(" dBm")) {
+            match signal.split("Signal level=").nth(1) {
+                Some(signal) => wifi.signal_level = signal.to_string(),
+                None => malformed = true,
+            }
+        } else if let Ok(key) = extract_value(line, "Encryption key:", None) {
+            encrypted = key == "on";
+        } else if let Ok(ssid) = extract_value(line, "ESSID:\"", Some("\"")) {
+            set_ssid(&mut wifi, &ssid);
+        } else if line.starts_with("IE: WPA Version") {
+            ie = "WPA";
+        } else if line.starts_with("IE: IEEE 802.11i/WPA2") {
+            ie = "WPA2";
+        } else if let Ok(suites) = extract_value(line, "Authentication Suites (", None) {
+            // mixed mode networks have both a WPA and a WPA2 element, as for `iw`
+            match suites.split(" : ").nth(1) {
+                Some(suites) => {
+                    if !wifi.security.is_empty() {
+                        wifi.security.push(' ');
+                    }
+                    wifi.security += &format!("{}({})", ie, suites);
+                }
+                None => malformed = true,
+            }
+        }
+    }
+    if !malformed {
+        push_iwlist_wifi(&mut wifis, wifi, encrypted);
     }
 
     Ok(wifis)
 }
 
+fn push_iwlist_wifi(wifis: &mut Vec<Wifi>, mut wifi: Wifi, encrypted: bool) {
+    if wifi.mac.is_empty()
+        || wifi.signal_level.is_empty()
+        || wifi.channel.is_empty()
+        || wifi.ssid.is_empty()
+    {
+        return;
+    }
+    // encrypted without any WPA/RSN authentication suites
+    if encrypted && wifi.security.is_empty() {
+        wifi.security = "WEP".to_string();
+    }
+    wifi.security = canonical_security(&wifi.security);
+    wifis.push(wifi);
+}
+
+fn parse_iw_dev_link(link: &str) -> Result<Option<Wifi>> {
+    let mut wifi = Wifi::default();
+    for line in link.lines() {
+        if let Ok(mac) = extract_value(line, "Connected to ", Some(" (on")) {
+            wifi.mac = mac;
+        } else if let Ok(signal) = extract_value(line, "\tsignal: ", Some(" dBm")) {
+            wifi.signal_level = signal;
+        } else if let Ok(ssid) = extract_value(line, "\tSSID: ", None) {
+            // trailing whitespace isn't part of the SSID, as in `parse_iw_dev_scan`
+            set_ssid(&mut wifi, ssid.trim_end());
+        } else if let Ok(frequency) = extract_value(line, "\tfreq: ", None) {
+            wifi.frequency = parse_frequency(&frequency);
+            if let Some(channel) = wifi.frequency.and_then(channel_from_frequency) {
+                wifi.channel = channel.to_string();
+            }
+        }
+    }
+
+    // `iw` prints "Not connected." when the interface isn't associated
+    if wifi.mac.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(wifi))
+    }
+}
+
+fn parse_iw_dev_link_info(link: &str) -> Result<Option<LinkInfo>> {
+    let network = match parse_iw_dev_link(link)? {
+        Some(network) => network,
+        None => return Ok(None),
+    };
+    let mut info = LinkInfo {
+        network,
+        ..Default::default()
+    };
+    for line in link.lines() {
+        if let Ok(bitrate) = extract_value(line, "\ttx bitrate: ", None) {
+            info.tx_bitrate_mbps = parse_bitrate(&bitrate);
+        } else if let Ok(bitrate) = extract_value(line, "\trx bitrate: ", None) {
+            info.rx_bitrate_mbps = parse_bitrate(&bitrate);
+        }
+    }
+
+    Ok(Some(info))
+}
+
+/// Sets the SSID from `iw` or `iwlist`, which print bytes that aren't printable ASCII
+/// (including UTF-8) as `\xNN` escapes.
+fn set_ssid(wifi: &mut Wifi, escaped: &str) {
+    let mut raw = Vec::with_capacity(escaped.len());
+    let mut rest = escaped;
+    while !rest.is_empty() {
+        let byte = rest
+            .strip_prefix("\\x")
+            .and_then(|hex| hex.get(..2))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match byte {
+            Some(byte) => {
+                raw.push(byte);
+                rest = &rest[4..];
+            }
+            None => {
+                let c = rest.chars().next().unwrap_or_default();
+                let mut buf = [0; 4];
+                raw.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    wifi.ssid = String::from_utf8_lossy(&raw).into_owned();
+    wifi.ssid_raw = raw;
+}
+
+/// Returns the number of spatial streams implied by the HT MCS indexes, as each stream
+/// adds eight indexes. Index 32 is a special 40 MHz duplicate mode, so it's ignored.
+fn ht_spatial_streams(indexes: &str) -> Option<u8> {
+    indexes
+        .split(',')
+        .filter_map(|range| range.rsplit('-').next()?.trim().parse::<u8>().ok())
+        .filter(|&index| index < 32)
+        .max()
+        .map(|index| index / 8 + 1)
+}
+
+/// Returns the signal to noise ratio, given a signal and noise level in dBm.
+fn snr_db(signal_dbm: Option<i32>, noise: &str) -> Option<i32> {
+    let noise_dbm = noise.trim().parse::<f64>().ok()?.round() as i32;
+    Some(signal_dbm? - noise_dbm)
+}
+
+/// Parses an `iw` frequency in MHz, which newer versions print with a fractional part,
+/// e.g. `2412.0`.
+fn parse_frequency(frequency: &str) -> Option<u32> {
+    frequency
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .map(|mhz| mhz.round() as u32)
+}
+
 fn extract_value(line: &str, pattern_start: &str, pattern_end: Option<&str>) -> Result<String> {
     let start = pattern_start.len();
     if start < line.len() && &line[0..start] == pattern_start {
         let end = match pattern_end {
-            Some(end) => line.find(end).ok_or(Error::NoValue)?,
+            Some(end) => start + line[start..].find(end).ok_or(Error::NoValue)?,
             None => line.len(),
         };
         Ok(line[start..end].to_string())
@@ -99,10 +831,15 @@ fn extract_value(line: &str, pattern_start: &str, pattern_end: Option<&str>) ->
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Band;
     use std::fs::File;
     use std::io::Read;
     use std::path::PathBuf;
 
+    fn fields(vendor_ies: bool) -> FieldSet {
+        ScanOptions::new().vendor_ies(vendor_ies).parsed_fields()
+    }
+
     #[test]
     fn should_parse_iw_dev() {
         let expected = "wlp2s0";
@@ -116,7 +853,7 @@ mod tests {
 
         let file_path = path.as_os_str();
 
-        let mut file = File::open(&file_path).unwrap();
+        let mut file = File::open(file_path).unwrap();
 
         let mut filestr = String::new();
         let _ = file.read_to_string(&mut filestr).unwrap();
@@ -125,24 +862,110 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn should_parse_iw_dev_interfaces() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_02.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        assert_eq!(
+            vec![
+                Interface {
+                    name: "wlan1mon".to_string(),
+                    ..Default::default()
+                },
+                Interface {
+                    name: "wlp2s0".to_string(),
+                    connected: true,
+                    ssid: Some("hello".to_string()),
+                    ..Default::default()
+                },
+            ],
+            parse_iw_dev_interfaces(&filestr)
+        );
+    }
+
+    #[test]
+    fn should_list_scan_commands() {
+        let options = ScanOptions::new().frequencies(vec![2412, 5180]);
+        let commands = scan_commands(&options);
+        assert_eq!("iw dev", commands[0]);
+        assert_eq!("iw dev <interface> scan freq 2412 5180", commands[1]);
+        assert_eq!("wpa_cli scan_results", commands[commands.len() - 2]);
+        assert_eq!(
+            "nmcli -t -f in-use,bssid,ssid,chan,freq,signal,security,rate device wifi list",
+            commands[commands.len() - 1]
+        );
+
+        let options = options.band(Band::FiveGhz);
+        assert_eq!(
+            "iw dev <interface> scan freq 5180",
+            scan_commands(&options)[1]
+        );
+    }
+
+    #[test]
+    fn should_skip_monitor_interfaces() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_02.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        assert_eq!("wlp2s0", parse_iw_dev(&filestr).unwrap());
+        assert_eq!(
+            Err(Error::NoWirelessInterface),
+            parse_iw_dev(filestr.split("phy#0").next().unwrap())
+        );
+    }
+
     #[test]
     fn should_parse_iw_dev_scan() {
-        let mut expected: Vec<Wifi> = Vec::new();
-        expected.push(Wifi {
-            mac: "11:22:33:44:55:66".to_string(),
-            ssid: "hello".to_string(),
-            channel: "10".to_string(),
-            signal_level: "-67.00".to_string(),
-            security: "".to_string(),
-        });
-
-        expected.push(Wifi {
-            mac: "66:77:88:99:aa:bb".to_string(),
-            ssid: "hello-world-foo-bar".to_string(),
-            channel: "8".to_string(),
-            signal_level: "-89.00".to_string(),
-            security: "".to_string(),
-        });
+        let expected = [
+            Wifi {
+                mac: "11:22:33:44:55:66".to_string(),
+                ssid: "hello".to_string(),
+                ssid_raw: b"hello".to_vec(),
+                channel: "10".to_string(),
+                signal_level: "-67.00".to_string(),
+                security: "WPA/WPA2-Personal".to_string(),
+                frequency: Some(2457),
+                last_seen_ms_ago: Some(5939),
+                capabilities: vec![
+                    "ESS".to_string(),
+                    "Privacy".to_string(),
+                    "ShortSlotTime".to_string(),
+                    "APSD".to_string(),
+                ],
+                akm_suites: vec!["PSK".to_string()],
+                spatial_streams: Some(2),
+                secondary_channel_offset: Some(0),
+                qos_enabled: true,
+                station_count: Some(2),
+                channel_utilization: Some(24),
+                ..Default::default()
+            },
+            Wifi {
+                mac: "66:77:88:99:aa:bb".to_string(),
+                ssid: "hello-world-foo-bar".to_string(),
+                ssid_raw: b"hello-world-foo-bar".to_vec(),
+                channel: "8".to_string(),
+                signal_level: "-89.00".to_string(),
+                security: "WPA/WPA2-Personal".to_string(),
+                frequency: Some(2447),
+                last_seen_ms_ago: Some(6248),
+                capabilities: vec![
+                    "ESS".to_string(),
+                    "Privacy".to_string(),
+                    "ShortSlotTime".to_string(),
+                ],
+                akm_suites: vec!["PSK".to_string()],
+                spatial_streams: Some(2),
+                secondary_channel_offset: Some(-1),
+                qos_enabled: true,
+                ..Default::default()
+            },
+        ];
 
         // FIXME: should be a better way to create test fixtures
         let mut path = PathBuf::new();
@@ -153,13 +976,505 @@ mod tests {
 
         let file_path = path.as_os_str();
 
-        let mut file = File::open(&file_path).unwrap();
+        let mut file = File::open(file_path).unwrap();
 
         let mut filestr = String::new();
         let _ = file.read_to_string(&mut filestr).unwrap();
 
-        let result = parse_iw_dev_scan(&filestr).unwrap();
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        let last = result.len() - 1;
         assert_eq!(expected[0], result[0]);
-        assert_eq!(expected[1], result[5]);
+        assert_eq!(expected[1], result[last]);
+    }
+
+    #[test]
+    fn should_iterate_iw_dev_scan() {
+        let output = fs::read_to_string("tests/fixtures/iw/iw_dev_scan_01.txt").unwrap();
+        let expected = parse_iw_dev_scan(&output, fields(false)).unwrap();
+
+        let iter = ScanIter::Iw {
+            output,
+            position: 0,
+            fields: fields(false),
+        };
+        assert_eq!(expected, iter.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_parse_iw_dev_scan_noise() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_scan_02.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        assert_eq!(Some(53), result[0].snr_db);
+        assert_eq!(None, result[1].snr_db);
+        assert_eq!(Some(0), result[0].last_seen_ms_ago);
+        assert_eq!(Some(5939), result[1].last_seen_ms_ago);
+    }
+
+    #[test]
+    fn should_parse_iw_dev_scan_capabilities() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_scan_03.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        assert_eq!(vec!["ESS", "Privacy"], result[0].capabilities);
+        assert!(result[0].has_privacy());
+        assert_eq!("WEP", result[0].security);
+        assert!(!result[1].has_privacy());
+        assert_eq!("Open", result[1].security);
+    }
+
+    #[test]
+    fn should_parse_iw_dev_scan_mixed_mode() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_scan_04.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        assert_eq!("WPA/WPA2-Personal", result[0].security);
+        assert_eq!("WPA-Personal", result[1].security);
+    }
+
+    #[test]
+    fn should_parse_iw_dev_scan_wpa3_transition() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_scan_05.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        assert_eq!(vec!["PSK", "SAE"], result[0].akm_suites);
+        assert_eq!("WPA3-Personal", result[0].security);
+        assert!(result[0].is_wpa3_transition());
+        assert_eq!("WPA3-Personal", result[1].security);
+        assert!(!result[1].is_wpa3_transition());
+        assert_eq!(vec!["IEEE 802.1X", "FT/IEEE 802.1X"], result[2].akm_suites);
+        assert_eq!("WPA2-Enterprise", result[2].security);
+        assert!(!result[2].is_wpa3_transition());
+    }
+
+    #[test]
+    fn should_parse_iw_dev_scan_ds_parameter_set() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_scan_06.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        assert_eq!(2, result.len());
+        assert_eq!("legacy-g", result[0].ssid);
+        assert_eq!("11", result[0].channel);
+        // the HT operation's primary channel wins over the DS parameter set
+        assert_eq!("6", result[1].channel);
+    }
+
+    #[test]
+    fn should_parse_iw_dev_scan_spatial_streams() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_scan_07.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        assert_eq!(Some(2), result[0].spatial_streams);
+        assert_eq!(Some(1), result[1].spatial_streams);
+    }
+
+    #[test]
+    fn should_count_ht_spatial_streams() {
+        assert_eq!(Some(1), ht_spatial_streams("0-7"));
+        assert_eq!(Some(2), ht_spatial_streams("0-15, 32"));
+        assert_eq!(Some(3), ht_spatial_streams("0-23"));
+        assert_eq!(None, ht_spatial_streams("32"));
+    }
+
+    #[test]
+    fn should_parse_iw_dev_scan_secondary_channel_offset() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_scan_08.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        assert_eq!(Some(1), result[0].secondary_channel_offset);
+        assert_eq!(Some(-1), result[1].secondary_channel_offset);
+        assert_eq!(Some(0), result[2].secondary_channel_offset);
+        // no HT operation element
+        assert_eq!(None, result[3].secondary_channel_offset);
+    }
+
+    #[test]
+    fn should_parse_iw_dev_scan_fast_transition() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_scan_09.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        assert_eq!(vec!["IEEE 802.1X", "FT/IEEE 802.1X"], result[0].akm_suites);
+        assert!(result[0].supports_fast_roaming());
+        assert!(result[1].supports_fast_roaming());
+        assert!(result[1].is_wpa3_transition());
+        assert!(!result[2].supports_fast_roaming());
+    }
+
+    #[test]
+    fn should_parse_iw_dev_scan_bss_color() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_scan_10.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        assert_eq!(Some(12), result[0].bss_color);
+        assert_eq!(Some(12), result[1].bss_color);
+        // disabled by the hotspot
+        assert_eq!(None, result[2].bss_color);
+        // no HE operation element
+        assert_eq!(None, result[3].bss_color);
+        assert_eq!(
+            vec![(result[0].clone(), result[1].clone())],
+            crate::color_conflicts(&result)
+        );
+    }
+
+    #[test]
+    fn should_parse_iw_dev_scan_wmm() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_scan_11.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        assert!(result[0].qos_enabled);
+        assert!(!result[1].qos_enabled);
+    }
+
+    #[test]
+    fn should_parse_iw_dev_scan_bss_load() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_scan_12.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        assert_eq!(Some(37), result[0].station_count);
+        assert_eq!(Some(204), result[0].channel_utilization);
+        assert_eq!(Some(3), result[1].station_count);
+        assert_eq!(Some(12), result[1].channel_utilization);
+        // no BSS load element
+        assert_eq!(None, result[2].station_count);
+        assert_eq!(None, result[2].channel_utilization);
+    }
+
+    #[test]
+    fn should_skip_unrequested_iw_dev_scan_fields() {
+        let filestr = fs::read_to_string("tests/fixtures/iw/iw_dev_scan_12.txt").unwrap();
+        let all = parse_iw_dev_scan(&filestr, FieldSet::all()).unwrap();
+        let minimal = parse_iw_dev_scan(&filestr, FieldSet::empty()).unwrap();
+        assert_eq!(all.len(), minimal.len());
+        assert_eq!(all[0].ssid, minimal[0].ssid);
+        assert_eq!(all[0].signal_level, minimal[0].signal_level);
+        assert_eq!(None, minimal[0].station_count);
+        assert_eq!(None, minimal[0].channel_utilization);
+
+        // WEP is still told from open networks without the capabilities
+        let filestr = fs::read_to_string("tests/fixtures/iw/iw_dev_scan_03.txt").unwrap();
+        let result = parse_iw_dev_scan(&filestr, FieldSet::BSS_LOAD).unwrap();
+        assert!(result[0].capabilities.is_empty());
+        assert_eq!("WEP", result[0].security);
+        assert_eq!("Open", result[1].security);
+    }
+
+    #[test]
+    fn should_parse_iw_dev_scan_vendor_ies() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_scan_13.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_scan(&filestr, fields(true)).unwrap();
+        assert_eq!(
+            vec!["0010180200001c0000", "000c4303000000"],
+            result[0].vendor_ies
+        );
+        assert!(result[0].qos_enabled);
+        assert!(result[1].vendor_ies.is_empty());
+
+        // only collected when asked for
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        assert!(result[0].vendor_ies.is_empty());
+    }
+
+    #[test]
+    fn should_merge_by_bssid() {
+        let iw = vec![
+            Wifi {
+                mac: "00:11:22:33:44:55".to_string(),
+                ssid: "home".to_string(),
+                signal_level: "-61.00".to_string(),
+                security: "WPA2-Personal".to_string(),
+                capabilities: vec!["ESS".to_string()],
+                ..Default::default()
+            },
+            Wifi {
+                mac: "66:77:88:99:aa:bb".to_string(),
+                ssid: "iw only".to_string(),
+                ..Default::default()
+            },
+        ];
+        let nmcli = vec![
+            Wifi {
+                mac: "00:11:22:33:44:55".to_string(),
+                ssid: "home".to_string(),
+                signal_level: "-60".to_string(),
+                security: "WPA2-Personal".to_string(),
+                max_rate_mbps: Some(270),
+                ..Default::default()
+            },
+            Wifi {
+                mac: "AA:BB:CC:DD:EE:FF".to_string(),
+                ssid: "nmcli only".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let merged = merge_by_bssid(iw.clone(), nmcli.clone());
+        assert_eq!(
+            vec![
+                Wifi {
+                    max_rate_mbps: Some(270),
+                    ..iw[0].clone()
+                },
+                iw[1].clone(),
+                nmcli[1].clone(),
+            ],
+            merged
+        );
+    }
+
+    #[test]
+    fn should_parse_iw_reg_get() {
+        let reg = std::fs::read_to_string("tests/fixtures/iw/iw_reg_get_01.txt").unwrap();
+        assert_eq!(Some("DE".to_string()), parse_iw_reg_get(&reg));
+        assert_eq!(None, parse_iw_reg_get(""));
+    }
+
+    #[test]
+    fn should_restore_reg_domain_after_failed_scan() {
+        let reg = std::fs::read_to_string("tests/fixtures/iw/iw_reg_get_01.txt").unwrap();
+        let options = ScanOptions::new()
+            .set_reg_domain("US")
+            .restore_reg_domain(true);
+        let mut calls = vec![];
+        let mut iw_reg = |args: &[&str]| {
+            calls.push(args.join(" "));
+            match args {
+                ["get"] => Ok(reg.clone()),
+                ["set", "DE"] => Err(Error::NeedsRoot("iw")),
+                _ => Ok(String::new()),
+            }
+        };
+
+        // the scan's error wins over the restore's
+        assert_eq!(
+            Err::<(), _>(Error::Cancelled),
+            with_reg_domain_using(&options, &mut iw_reg, || Err(Error::Cancelled))
+        );
+        assert_eq!(
+            Err(Error::NeedsRoot("iw")),
+            with_reg_domain_using(&options, &mut iw_reg, || Ok(()))
+        );
+        assert_eq!(
+            vec!["get", "set US", "set DE", "get", "set US", "set DE"],
+            calls
+        );
+    }
+
+    #[test]
+    fn should_not_set_reg_domain_it_cannot_restore() {
+        let options = ScanOptions::new()
+            .set_reg_domain("US")
+            .restore_reg_domain(true);
+        let mut calls = vec![];
+        let iw_reg = |args: &[&str]| {
+            calls.push(args.join(" "));
+            Ok(String::new())
+        };
+
+        assert_eq!(
+            Err(Error::FailedToParse),
+            with_reg_domain_using(&options, iw_reg, || Ok(()))
+        );
+        assert_eq!(vec!["get"], calls);
+    }
+
+    #[test]
+    fn should_warn_about_unparsed_iw_output() {
+        // e.g. a future `iw` renaming the signal line
+        let output = "BSS 00:11:22:33:44:55(on wlan0)\n\tfreq: 2412\n\tsignal strength: -45 dBm\n\
+                      BSS 66:77:88:99:aa:bb(on wlan0)\n\tfreq: 2437\n";
+        let wifis = parse_iw_dev_scan(output, fields(false)).unwrap();
+        let report = ScanReport::checked(ScanResult::fresh(wifis), output, is_iw_hotspot_line);
+        assert_eq!(
+            vec![crate::ScanWarning::UnparsedOutput { lines: 2 }],
+            report.warnings
+        );
+
+        let report = ScanReport::checked(ScanResult::fresh(vec![]), "", is_iw_hotspot_line);
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_trim_trailing_whitespace_from_iw_ssid() {
+        let fixture = std::fs::read_to_string("tests/fixtures/iw/iw_dev_scan_15.txt").unwrap();
+
+        let result = parse_iw_dev_scan(&fixture, fields(false)).unwrap();
+        let ssids: Vec<_> = result.iter().map(|wifi| wifi.ssid.as_str()).collect();
+        assert_eq!(vec!["home", "my cafe", " padded "], ssids);
+        assert_eq!(b"my cafe".to_vec(), result[1].ssid_raw);
+    }
+
+    #[test]
+    fn should_parse_iw_dev_scan_oversized_ssid() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_scan_14.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_scan(&filestr, fields(false)).unwrap();
+        assert_eq!(33, result[0].ssid_byte_len());
+        assert!(!result[0].has_valid_ssid_length());
+        // `é` is two bytes
+        assert_eq!(32, result[1].ssid_byte_len());
+        assert!(result[1].has_valid_ssid_length());
+    }
+
+    #[test]
+    fn should_split_akm_suites() {
+        assert_eq!(vec!["PSK", "SAE"], split_akm_suites("PSK SAE"));
+        assert_eq!(
+            vec!["IEEE 802.1X", "FT/IEEE 802.1X", "PSK"],
+            split_akm_suites("IEEE 802.1X FT/IEEE 802.1X PSK")
+        );
+    }
+
+    #[test]
+    fn should_unescape_ssid() {
+        let mut wifi = Wifi::default();
+        set_ssid(&mut wifi, "caf\\xc3\\xa9\\x5cbar");
+        assert_eq!("café\\bar", wifi.ssid);
+        assert_eq!(b"caf\xc3\xa9\\bar".to_vec(), wifi.ssid_raw);
+
+        set_ssid(&mut wifi, "bad\\xff\\x2");
+        assert_eq!("bad\u{fffd}\\x2", wifi.ssid);
+        assert_eq!(b"bad\xff\\x2".to_vec(), wifi.ssid_raw);
+    }
+
+    #[test]
+    fn should_parse_iw_dev_link() {
+        let expected = Wifi {
+            mac: "22:33:44:55:66:77".to_string(),
+            ssid: "world".to_string(),
+            ssid_raw: b"world".to_vec(),
+            channel: "1".to_string(),
+            signal_level: "-42".to_string(),
+            frequency: Some(2412),
+            ..Default::default()
+        };
+
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_link_01.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let result = parse_iw_dev_link(&filestr).unwrap();
+        assert_eq!(Some(expected), result);
+    }
+
+    #[test]
+    fn should_parse_iw_dev_link_not_connected() {
+        assert_eq!(None, parse_iw_dev_link("Not connected.\n").unwrap());
+        assert_eq!(None, parse_iw_dev_link_info("Not connected.\n").unwrap());
+    }
+
+    #[test]
+    fn should_parse_iw_dev_link_info() {
+        let path = PathBuf::from("tests/fixtures/iw/iw_dev_link_01.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let info = parse_iw_dev_link_info(&filestr).unwrap().unwrap();
+        assert_eq!("22:33:44:55:66:77", info.network.mac);
+        assert_eq!(Some(-42), info.network.signal_dbm());
+        assert_eq!(Some(65.0), info.tx_bitrate_mbps);
+        assert_eq!(Some(72.2), info.rx_bitrate_mbps);
+    }
+
+    #[test]
+    fn should_parse_iwlist_scanning() {
+        let expected = [
+            Wifi {
+                mac: "11:22:33:44:55:66".to_string(),
+                ssid: "hello".to_string(),
+                ssid_raw: b"hello".to_vec(),
+                channel: "10".to_string(),
+                signal_level: "-67".to_string(),
+                // both a WPA and a WPA2 element
+                security: "WPA/WPA2-Personal".to_string(),
+                frequency: Some(2457),
+                ..Default::default()
+            },
+            Wifi {
+                mac: "22:33:44:55:66:77".to_string(),
+                ssid: "world".to_string(),
+                ssid_raw: b"world".to_vec(),
+                channel: "1".to_string(),
+                signal_level: "-42".to_string(),
+                security: "Open".to_string(),
+                frequency: Some(2412),
+                ..Default::default()
+            },
+            Wifi {
+                mac: "33:44:55:66:77:88".to_string(),
+                ssid: "old school".to_string(),
+                ssid_raw: b"old school".to_vec(),
+                channel: "36".to_string(),
+                signal_level: "-75".to_string(),
+                security: "WEP".to_string(),
+                frequency: Some(5180),
+                ..Default::default()
+            },
+        ];
+
+        let path = PathBuf::from("tests/fixtures/iwlist/iwlist_scanning_01.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        // the last cell has a hidden SSID so is skipped like it is for `iw`
+        let result = parse_iwlist_scanning(&filestr).unwrap();
+        assert_eq!(expected[..], result[..]);
+    }
+
+    #[test]
+    fn should_skip_malformed_iwlist_cells() {
+        let list = std::fs::read_to_string("tests/fixtures/iwlist/iwlist_scanning_02.txt").unwrap();
+
+        let result = parse_iwlist_scanning(&list).unwrap();
+        assert_eq!(
+            vec!["11:22:33:44:55:66", "55:66:77:88:99:aa"],
+            result
+                .iter()
+                .map(|wifi| wifi.mac.as_str())
+                .collect::<Vec<_>>()
+        );
     }
 }
@@ -0,0 +1,166 @@
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::OwnedObjectPath;
+
+use crate::channel::channel_from_frequency;
+use crate::security::canonical_security;
+use crate::{SignalBounds, Wifi};
+
+const NM_DESTINATION: &str = "org.freedesktop.NetworkManager";
+const NM_DEVICE_TYPE_WIFI: u32 = 2;
+
+// NM80211ApFlags and NM80211ApSecurityFlags
+const NM_AP_FLAGS_PRIVACY: u32 = 0x1;
+const NM_AP_SEC_KEY_MGMT: [(u32, &str); 4] = [
+    (0x100, "PSK"),
+    (0x200, "IEEE 802.1X"),
+    (0x400, "SAE"),
+    (0x800, "OWE"),
+];
+
+/// Access point properties as exposed by NetworkManager's
+/// `org.freedesktop.NetworkManager.AccessPoint` interface.
+struct AccessPoint {
+    ssid: Vec<u8>,
+    hw_address: String,
+    strength: u8,
+    frequency: u32,
+    flags: u32,
+    wpa_flags: u32,
+    rsn_flags: u32,
+}
+
+/// Returns a list of WiFi hotspots known to NetworkManager - (Linux) uses D-Bus
+pub(crate) fn scan(bounds: SignalBounds) -> zbus::Result<Vec<Wifi>> {
+    let connection = Connection::system()?;
+    let network_manager = Proxy::new(
+        &connection,
+        NM_DESTINATION,
+        "/org/freedesktop/NetworkManager",
+        "org.freedesktop.NetworkManager",
+    )?;
+
+    let mut wifis = Vec::new();
+    let devices: Vec<OwnedObjectPath> = network_manager.call("GetDevices", &())?;
+    for device in devices {
+        let device_type: u32 = Proxy::new(
+            &connection,
+            NM_DESTINATION,
+            device.as_str(),
+            "org.freedesktop.NetworkManager.Device",
+        )?
+        .get_property("DeviceType")?;
+        if device_type != NM_DEVICE_TYPE_WIFI {
+            continue;
+        }
+
+        let wireless = Proxy::new(
+            &connection,
+            NM_DESTINATION,
+            device.as_str(),
+            "org.freedesktop.NetworkManager.Device.Wireless",
+        )?;
+        let access_points: Vec<OwnedObjectPath> = wireless.call("GetAllAccessPoints", &())?;
+        for access_point in access_points {
+            let properties = Proxy::new(
+                &connection,
+                NM_DESTINATION,
+                access_point.as_str(),
+                "org.freedesktop.NetworkManager.AccessPoint",
+            )?;
+            wifis.push(to_wifi(
+                &AccessPoint {
+                    ssid: properties.get_property("Ssid")?,
+                    hw_address: properties.get_property("HwAddress")?,
+                    strength: properties.get_property("Strength")?,
+                    frequency: properties.get_property("Frequency")?,
+                    flags: properties.get_property("Flags")?,
+                    wpa_flags: properties.get_property("WpaFlags")?,
+                    rsn_flags: properties.get_property("RsnFlags")?,
+                },
+                bounds,
+            ));
+        }
+    }
+
+    Ok(wifis)
+}
+
+fn to_wifi(access_point: &AccessPoint, bounds: SignalBounds) -> Wifi {
+    let key_management = |flags: u32| {
+        NM_AP_SEC_KEY_MGMT
+            .iter()
+            .filter(|(flag, _)| flags & flag != 0)
+            .map(|(_, name)| name.to_string())
+            .collect::<Vec<_>>()
+    };
+    let mut security = key_management(access_point.wpa_flags | access_point.rsn_flags).join(" ");
+    // encrypted without any WPA/RSN key management
+    if security.is_empty() && access_point.flags & NM_AP_FLAGS_PRIVACY != 0 {
+        security = "WEP".to_string();
+    }
+
+    Wifi {
+        mac: access_point.hw_address.to_lowercase(),
+        ssid: String::from_utf8_lossy(&access_point.ssid).to_string(),
+        ssid_raw: access_point.ssid.clone(),
+        channel: channel_from_frequency(access_point.frequency)
+            .map(|channel| channel.to_string())
+            .unwrap_or_default(),
+        // NetworkManager reports strength as a percentage, converted like `netsh`'s signal
+        signal_level: bounds.to_dbm(access_point.strength).to_string(),
+        security: canonical_security(&security),
+        frequency: Some(access_point.frequency),
+        akm_suites: key_management(access_point.rsn_flags),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_access_point() {
+        let access_point = AccessPoint {
+            ssid: b"hello".to_vec(),
+            hw_address: "11:22:33:44:55:66".to_string(),
+            strength: 66,
+            frequency: 2457,
+            flags: NM_AP_FLAGS_PRIVACY,
+            wpa_flags: 0x100,
+            rsn_flags: 0x100 | 0x400,
+        };
+
+        assert_eq!(
+            Wifi {
+                mac: "11:22:33:44:55:66".to_string(),
+                ssid: "hello".to_string(),
+                ssid_raw: b"hello".to_vec(),
+                channel: "10".to_string(),
+                signal_level: "-67".to_string(),
+                security: "WPA3-Personal".to_string(),
+                frequency: Some(2457),
+                akm_suites: vec!["PSK".to_string(), "SAE".to_string()],
+                ..Default::default()
+            },
+            to_wifi(&access_point, SignalBounds::default())
+        );
+    }
+
+    #[test]
+    fn should_convert_wep_access_point() {
+        let access_point = AccessPoint {
+            ssid: b"old school".to_vec(),
+            hw_address: "33:44:55:66:77:88".to_string(),
+            strength: 50,
+            frequency: 5180,
+            flags: NM_AP_FLAGS_PRIVACY,
+            wpa_flags: 0,
+            rsn_flags: 0,
+        };
+
+        let wifi = to_wifi(&access_point, SignalBounds::default());
+        assert_eq!("36", wifi.channel);
+        assert_eq!("WEP", wifi.security);
+    }
+}
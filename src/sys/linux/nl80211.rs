@@ -0,0 +1,632 @@
+//! A minimal generic netlink client speaking just enough nl80211 to trigger a scan and
+//! read back its results, see `include/uapi/linux/nl80211.h` in the kernel sources.
+
+use std::io;
+use std::mem;
+
+use crate::channel::channel_from_frequency;
+use crate::security::canonical_security;
+use crate::Wifi;
+
+const NLMSG_HEADER_LEN: usize = 16;
+const GENL_HEADER_LEN: usize = 4;
+
+const NLM_F_REQUEST: u16 = 0x1;
+const NLM_F_ACK: u16 = 0x4;
+const NLM_F_DUMP: u16 = 0x300;
+const NLMSG_ERROR: u16 = 0x2;
+const NLMSG_DONE: u16 = 0x3;
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
+const GENL_ID_CTRL: u16 = 0x10;
+const CTRL_CMD_GETFAMILY: u8 = 3;
+const CTRL_ATTR_FAMILY_ID: u16 = 1;
+const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+const CTRL_ATTR_MCAST_GROUPS: u16 = 7;
+const CTRL_ATTR_MCAST_GRP_NAME: u16 = 1;
+const CTRL_ATTR_MCAST_GRP_ID: u16 = 2;
+
+const NL80211_CMD_GET_INTERFACE: u8 = 5;
+const NL80211_CMD_GET_SCAN: u8 = 32;
+const NL80211_CMD_TRIGGER_SCAN: u8 = 33;
+const NL80211_CMD_NEW_SCAN_RESULTS: u8 = 34;
+const NL80211_CMD_SCAN_ABORTED: u8 = 35;
+const NL80211_ATTR_IFINDEX: u16 = 3;
+const NL80211_ATTR_IFTYPE: u16 = 5;
+const NL80211_ATTR_SCAN_FREQUENCIES: u16 = 44;
+const NL80211_ATTR_SCAN_SSIDS: u16 = 45;
+const NL80211_ATTR_BSS: u16 = 47;
+const NL80211_IFTYPE_STATION: u32 = 2;
+const NL80211_BSS_BSSID: u16 = 1;
+const NL80211_BSS_FREQUENCY: u16 = 2;
+const NL80211_BSS_CAPABILITY: u16 = 5;
+const NL80211_BSS_INFORMATION_ELEMENTS: u16 = 6;
+const NL80211_BSS_SIGNAL_MBM: u16 = 7;
+const NL80211_BSS_SEEN_MS_AGO: u16 = 10;
+
+// 802.11 information elements
+const IE_SSID: u8 = 0;
+const IE_RSN: u8 = 48;
+const IE_VENDOR_SPECIFIC: u8 = 221;
+const WPA_OUI_TYPE: [u8; 4] = [0x00, 0x50, 0xf2, 0x01];
+// 802.11 capability information bit set by hotspots requiring encryption
+const CAPABILITY_PRIVACY: u16 = 0x10;
+
+/// How long to wait for the kernel to finish a scan.
+const SCAN_TIMEOUT_SECS: libc::time_t = 10;
+
+/// Returns a list of WiFi hotspots in your area - (Linux) uses nl80211
+///
+/// Triggering a scan needs `CAP_NET_ADMIN`. Without it the hotspots from the kernel's
+/// last scan are returned, which may be stale.
+///
+/// Only the given `frequencies` in MHz are scanned, unless it's empty. Also returns
+/// whether a scan completed, i.e. whether the hotspots are fresh.
+pub(crate) fn scan(frequencies: &[u32]) -> io::Result<(Vec<Wifi>, bool)> {
+    let mut socket = Socket::open()?;
+    let (family, scan_group) = nl80211_family(&mut socket)?;
+    let interface = station_interface(&mut socket, family)?;
+    let interface_attribute = attribute(NL80211_ATTR_IFINDEX, &interface.to_ne_bytes());
+
+    // subscribe before triggering, so the scan can't finish before we're listening
+    let events = Socket::open()?;
+    events.join_group(scan_group)?;
+
+    // an empty SSID requests an active scan for every network, as `iw` does
+    let mut trigger = interface_attribute.clone();
+    trigger.extend(attribute(NL80211_ATTR_SCAN_SSIDS, &attribute(1, &[])));
+    if !frequencies.is_empty() {
+        let nested = frequencies
+            .iter()
+            .zip(1..)
+            .flat_map(|(frequency, index)| attribute(index, &frequency.to_ne_bytes()))
+            .collect::<Vec<_>>();
+        trigger.extend(attribute(NL80211_ATTR_SCAN_FREQUENCIES, &nested));
+    }
+    let was_fresh = match socket.request(family, NLM_F_ACK, NL80211_CMD_TRIGGER_SCAN, &trigger) {
+        Ok(_) => wait_for_scan(&events, interface)?,
+        // someone else's scan is already running, so wait for its results
+        Err(ref err) if err.raw_os_error() == Some(libc::EBUSY) => {
+            wait_for_scan(&events, interface)?
+        }
+        // not allowed to scan, fall back to the cached results
+        Err(_) => false,
+    };
+
+    let wifis = scan_results(&mut socket, family, &interface_attribute)?;
+    Ok((wifis, was_fresh))
+}
+
+/// Returns the hotspots from the kernel's last scan without triggering a new one.
+pub(crate) fn scan_cached() -> io::Result<Vec<Wifi>> {
+    let mut socket = Socket::open()?;
+    let (family, _) = nl80211_family(&mut socket)?;
+    let interface = station_interface(&mut socket, family)?;
+
+    scan_results(
+        &mut socket,
+        family,
+        &attribute(NL80211_ATTR_IFINDEX, &interface.to_ne_bytes()),
+    )
+}
+
+fn scan_results(
+    socket: &mut Socket,
+    family: u16,
+    interface_attribute: &[u8],
+) -> io::Result<Vec<Wifi>> {
+    let messages = socket.request(
+        family,
+        NLM_F_DUMP,
+        NL80211_CMD_GET_SCAN,
+        interface_attribute,
+    )?;
+    Ok(messages
+        .iter()
+        .filter_map(|message| find_attribute(&message.attributes, NL80211_ATTR_BSS))
+        .filter_map(parse_bss)
+        .collect())
+}
+
+/// Returns the nl80211 family id and the id of its `scan` multicast group.
+fn nl80211_family(socket: &mut Socket) -> io::Result<(u16, u32)> {
+    let messages = socket.request(
+        GENL_ID_CTRL,
+        NLM_F_ACK,
+        CTRL_CMD_GETFAMILY,
+        &attribute(CTRL_ATTR_FAMILY_NAME, b"nl80211\0"),
+    )?;
+    let attributes = &messages.first().ok_or_else(not_found)?.attributes;
+
+    let family = find_attribute(attributes, CTRL_ATTR_FAMILY_ID)
+        .and_then(read_u16)
+        .ok_or_else(not_found)?;
+    let groups = find_attribute(attributes, CTRL_ATTR_MCAST_GROUPS).ok_or_else(not_found)?;
+    let scan_group = parse_attributes(groups)
+        .into_iter()
+        .map(|(_, group)| group)
+        .find(|group| find_attribute(group, CTRL_ATTR_MCAST_GRP_NAME) == Some(b"scan\0"))
+        .and_then(|group| find_attribute(group, CTRL_ATTR_MCAST_GRP_ID))
+        .and_then(read_u32)
+        .ok_or_else(not_found)?;
+
+    Ok((family, scan_group))
+}
+
+/// Returns the index of the first interface in station (managed) mode.
+fn station_interface(socket: &mut Socket, family: u16) -> io::Result<u32> {
+    socket
+        .request(family, NLM_F_DUMP, NL80211_CMD_GET_INTERFACE, &[])?
+        .iter()
+        .find(|message| {
+            find_attribute(&message.attributes, NL80211_ATTR_IFTYPE).and_then(read_u32)
+                == Some(NL80211_IFTYPE_STATION)
+        })
+        .and_then(|message| find_attribute(&message.attributes, NL80211_ATTR_IFINDEX))
+        .and_then(read_u32)
+        .ok_or_else(not_found)
+}
+
+/// Waits for a scan to finish, returning false if it was aborted.
+fn wait_for_scan(events: &Socket, interface: u32) -> io::Result<bool> {
+    loop {
+        for (_, message) in parse_messages(&events.receive()?)? {
+            let message = match message {
+                Reply::Message(message) => message,
+                _ => continue,
+            };
+            let finished = message.command == NL80211_CMD_NEW_SCAN_RESULTS
+                || message.command == NL80211_CMD_SCAN_ABORTED;
+            let interface_matches = find_attribute(&message.attributes, NL80211_ATTR_IFINDEX)
+                .and_then(read_u32)
+                == Some(interface);
+            if finished && interface_matches {
+                return Ok(message.command == NL80211_CMD_NEW_SCAN_RESULTS);
+            }
+        }
+    }
+}
+
+fn parse_bss(bss: &[u8]) -> Option<Wifi> {
+    let mac = find_attribute(bss, NL80211_BSS_BSSID)?
+        .iter()
+        .map(|octet| format!("{:02x}", octet))
+        .collect::<Vec<_>>()
+        .join(":");
+    let frequency = find_attribute(bss, NL80211_BSS_FREQUENCY).and_then(read_u32);
+    let channel = frequency
+        .and_then(channel_from_frequency)
+        .map(|channel| channel.to_string())
+        .unwrap_or_default();
+    // mBm is 100 * dBm, formatted the same way as `iw`
+    let signal_level = find_attribute(bss, NL80211_BSS_SIGNAL_MBM)
+        .and_then(read_u32)
+        .map(|mbm| format!("{:.2}", f64::from(mbm as i32) / 100.0))
+        .unwrap_or_default();
+    let elements = find_attribute(bss, NL80211_BSS_INFORMATION_ELEMENTS).unwrap_or(&[]);
+    let (ssid_raw, mut security, akm_suites) = parse_information_elements(elements);
+    // encrypted without a WPA or RSN element, like `iw`'s `Privacy` capability
+    let privacy = find_attribute(bss, NL80211_BSS_CAPABILITY)
+        .and_then(read_u16)
+        .is_some_and(|capability| capability & CAPABILITY_PRIVACY != 0);
+    if security.is_empty() && privacy {
+        security = "WEP".to_string();
+    }
+
+    Some(Wifi {
+        mac,
+        ssid: String::from_utf8_lossy(&ssid_raw).to_string(),
+        ssid_raw,
+        channel,
+        signal_level,
+        security: canonical_security(&security),
+        frequency,
+        akm_suites,
+        last_seen_ms_ago: find_attribute(bss, NL80211_BSS_SEEN_MS_AGO)
+            .and_then(read_u32)
+            .map(u64::from),
+        ..Default::default()
+    })
+}
+
+/// Returns the SSID, the authentication suites named as `iw` names them and the RSN
+/// element's suites on their own.
+fn parse_information_elements(mut elements: &[u8]) -> (Vec<u8>, String, Vec<String>) {
+    let mut ssid = Vec::new();
+    let mut rsn_suites = None;
+    let mut wpa_suites = None;
+
+    while elements.len() >= 2 {
+        let (id, len) = (elements[0], usize::from(elements[1]));
+        if elements.len() < 2 + len {
+            break;
+        }
+        let data = &elements[2..2 + len];
+        match id {
+            IE_SSID => ssid = data.to_vec(),
+            IE_RSN => rsn_suites = authentication_suites(data),
+            IE_VENDOR_SPECIFIC if data.starts_with(&WPA_OUI_TYPE) => {
+                wpa_suites = authentication_suites(&data[WPA_OUI_TYPE.len()..])
+            }
+            _ => (),
+        }
+        elements = &elements[2 + len..];
+    }
+
+    let akm_suites = rsn_suites
+        .as_deref()
+        .map(super::split_akm_suites)
+        .unwrap_or_default();
+    (
+        ssid,
+        rsn_suites.or(wpa_suites).unwrap_or_default(),
+        akm_suites,
+    )
+}
+
+/// Parses the authentication (AKM) suites from the body of an RSN or WPA element:
+/// version, group cipher, pairwise ciphers then authentication suites.
+fn authentication_suites(element: &[u8]) -> Option<String> {
+    let pairwise_count = usize::from(read_u16(element.get(6..8)?)?);
+    let akm_start = 8 + 4 * pairwise_count;
+    let akm_count = usize::from(read_u16(element.get(akm_start..akm_start + 2)?)?);
+    let suites = element.get(akm_start + 2..akm_start + 2 + 4 * akm_count)?;
+
+    Some(
+        suites
+            .chunks(4)
+            .map(|suite| match suite[3] {
+                1 => "IEEE 802.1X".to_string(),
+                2 => "PSK".to_string(),
+                3 => "FT/IEEE 802.1X".to_string(),
+                4 => "FT/PSK".to_string(),
+                5 => "IEEE 802.1X/SHA-256".to_string(),
+                6 => "PSK/SHA-256".to_string(),
+                8 => "SAE".to_string(),
+                9 => "FT/SAE".to_string(),
+                18 => "OWE".to_string(),
+                other => format!(
+                    "{:02X}-{:02X}-{:02X}:{}",
+                    suite[0], suite[1], suite[2], other
+                ),
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+fn not_found() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        "nl80211 reply is missing attributes",
+    )
+}
+
+/// A generic netlink message addressed to us.
+struct Message {
+    command: u8,
+    attributes: Vec<u8>,
+}
+
+enum Reply {
+    Message(Message),
+    /// an acknowledgement (0) or a negated errno
+    Error(i32),
+    Done,
+}
+
+/// Splits a datagram into its netlink messages, along with their sequence numbers.
+fn parse_messages(mut buf: &[u8]) -> io::Result<Vec<(u32, Reply)>> {
+    let mut replies = Vec::new();
+    while buf.len() >= NLMSG_HEADER_LEN {
+        let len = read_u32(&buf[0..4]).unwrap_or(0) as usize;
+        if len < NLMSG_HEADER_LEN || len > buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "truncated netlink message",
+            ));
+        }
+        let kind = read_u16(&buf[4..6]).unwrap_or(0);
+        let sequence = read_u32(&buf[8..12]).unwrap_or(0);
+        let payload = &buf[NLMSG_HEADER_LEN..len];
+        buf = &buf[align(len).min(buf.len())..];
+
+        let reply = match kind {
+            NLMSG_ERROR => Reply::Error(payload.get(0..4).and_then(read_u32).unwrap_or(0) as i32),
+            NLMSG_DONE => Reply::Done,
+            _ if payload.len() >= GENL_HEADER_LEN => Reply::Message(Message {
+                command: payload[0],
+                attributes: payload[GENL_HEADER_LEN..].to_vec(),
+            }),
+            _ => continue,
+        };
+        replies.push((sequence, reply));
+    }
+    Ok(replies)
+}
+
+fn attribute(kind: u16, payload: &[u8]) -> Vec<u8> {
+    let len = 4 + payload.len();
+    let mut buf = Vec::with_capacity(align(len));
+    buf.extend_from_slice(&(len as u16).to_ne_bytes());
+    buf.extend_from_slice(&kind.to_ne_bytes());
+    buf.extend_from_slice(payload);
+    buf.resize(align(len), 0);
+    buf
+}
+
+fn parse_attributes(mut buf: &[u8]) -> Vec<(u16, &[u8])> {
+    let mut attributes = Vec::new();
+    while buf.len() >= 4 {
+        let len = usize::from(read_u16(&buf[0..2]).unwrap_or(0));
+        if len < 4 || len > buf.len() {
+            break;
+        }
+        let kind = read_u16(&buf[2..4]).unwrap_or(0) & NLA_TYPE_MASK;
+        attributes.push((kind, &buf[4..len]));
+        buf = &buf[align(len).min(buf.len())..];
+    }
+    attributes
+}
+
+fn find_attribute(attributes: &[u8], kind: u16) -> Option<&[u8]> {
+    parse_attributes(attributes)
+        .into_iter()
+        .find(|(attribute_kind, _)| *attribute_kind == kind)
+        .map(|(_, payload)| payload)
+}
+
+fn read_u16(buf: &[u8]) -> Option<u16> {
+    let mut bytes = [0; 2];
+    bytes.copy_from_slice(buf.get(0..2)?);
+    Some(u16::from_ne_bytes(bytes))
+}
+
+fn read_u32(buf: &[u8]) -> Option<u32> {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(buf.get(0..4)?);
+    Some(u32::from_ne_bytes(bytes))
+}
+
+/// Netlink pads messages and attributes to four bytes.
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+struct Socket {
+    fd: libc::c_int,
+    sequence: u32,
+}
+
+impl Socket {
+    fn open() -> io::Result<Socket> {
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::NETLINK_GENERIC,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let socket = Socket { fd, sequence: 0 };
+
+        let mut address: libc::sockaddr_nl = unsafe { mem::zeroed() };
+        address.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        let timeout = libc::timeval {
+            tv_sec: SCAN_TIMEOUT_SECS,
+            tv_usec: 0,
+        };
+        unsafe {
+            if libc::bind(
+                fd,
+                &address as *const libc::sockaddr_nl as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            ) < 0
+                || libc::setsockopt(
+                    fd,
+                    libc::SOL_SOCKET,
+                    libc::SO_RCVTIMEO,
+                    &timeout as *const libc::timeval as *const libc::c_void,
+                    mem::size_of::<libc::timeval>() as libc::socklen_t,
+                ) < 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        Ok(socket)
+    }
+
+    fn join_group(&self, group: u32) -> io::Result<()> {
+        let result = unsafe {
+            libc::setsockopt(
+                self.fd,
+                libc::SOL_NETLINK,
+                libc::NETLINK_ADD_MEMBERSHIP,
+                &group as *const u32 as *const libc::c_void,
+                mem::size_of::<u32>() as libc::socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Sends a request and collects the messages sent in reply, until the kernel
+    /// acknowledges it or finishes the dump.
+    fn request(
+        &mut self,
+        family: u16,
+        flags: u16,
+        command: u8,
+        attributes: &[u8],
+    ) -> io::Result<Vec<Message>> {
+        self.sequence += 1;
+        let len = NLMSG_HEADER_LEN + GENL_HEADER_LEN + attributes.len();
+        let mut buf = Vec::with_capacity(len);
+        buf.extend_from_slice(&(len as u32).to_ne_bytes());
+        buf.extend_from_slice(&family.to_ne_bytes());
+        buf.extend_from_slice(&(NLM_F_REQUEST | flags).to_ne_bytes());
+        buf.extend_from_slice(&self.sequence.to_ne_bytes());
+        buf.extend_from_slice(&0u32.to_ne_bytes());
+        buf.extend_from_slice(&[command, 1, 0, 0]);
+        buf.extend_from_slice(attributes);
+
+        let sent =
+            unsafe { libc::send(self.fd, buf.as_ptr() as *const libc::c_void, buf.len(), 0) };
+        if sent < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut messages = Vec::new();
+        loop {
+            for (sequence, reply) in parse_messages(&self.receive()?)? {
+                if sequence != self.sequence {
+                    continue;
+                }
+                match reply {
+                    Reply::Message(message) => messages.push(message),
+                    Reply::Error(0) | Reply::Done => return Ok(messages),
+                    Reply::Error(errno) => return Err(io::Error::from_raw_os_error(-errno)),
+                }
+            }
+        }
+    }
+
+    fn receive(&self) -> io::Result<Vec<u8>> {
+        let mut buf = vec![0; 64 * 1024];
+        let received =
+            unsafe { libc::recv(self.fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+        if received < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        buf.truncate(received as usize);
+        Ok(buf)
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn information_element(id: u8, data: &[u8]) -> Vec<u8> {
+        let mut element = vec![id, data.len() as u8];
+        element.extend_from_slice(data);
+        element
+    }
+
+    #[test]
+    fn should_parse_bss() {
+        // RSN: version 1, group CCMP, 1 pairwise (CCMP), 2 AKMs (PSK, SAE), capabilities
+        let rsn = [
+            1, 0, 0x00, 0x0f, 0xac, 4, 1, 0, 0x00, 0x0f, 0xac, 4, 2, 0, 0x00, 0x0f, 0xac, 2, 0x00,
+            0x0f, 0xac, 8, 0, 0,
+        ];
+        let mut elements = information_element(IE_SSID, b"hello");
+        elements.extend(information_element(3, &[10]));
+        elements.extend(information_element(IE_RSN, &rsn));
+
+        let mut bss = attribute(NL80211_BSS_BSSID, &[0x11, 0x22, 0x33, 0x44, 0x55, 0x66]);
+        bss.extend(attribute(NL80211_BSS_FREQUENCY, &2457u32.to_ne_bytes()));
+        bss.extend(attribute(NL80211_BSS_INFORMATION_ELEMENTS, &elements));
+        bss.extend(attribute(NL80211_BSS_SIGNAL_MBM, &(-6700i32).to_ne_bytes()));
+        bss.extend(attribute(NL80211_BSS_SEEN_MS_AGO, &5939u32.to_ne_bytes()));
+
+        assert_eq!(
+            Some(Wifi {
+                mac: "11:22:33:44:55:66".to_string(),
+                ssid: "hello".to_string(),
+                ssid_raw: b"hello".to_vec(),
+                channel: "10".to_string(),
+                signal_level: "-67.00".to_string(),
+                security: "WPA3-Personal".to_string(),
+                frequency: Some(2457),
+                last_seen_ms_ago: Some(5939),
+                akm_suites: vec!["PSK".to_string(), "SAE".to_string()],
+                ..Default::default()
+            }),
+            parse_bss(&bss)
+        );
+    }
+
+    #[test]
+    fn should_tell_wep_from_open_bss() {
+        let bss = |capability: u16| {
+            let mut bss = attribute(NL80211_BSS_BSSID, &[0x33, 0x44, 0x55, 0x66, 0x77, 0x88]);
+            bss.extend(attribute(NL80211_BSS_CAPABILITY, &capability.to_ne_bytes()));
+            bss.extend(attribute(
+                NL80211_BSS_INFORMATION_ELEMENTS,
+                &information_element(IE_SSID, b"old school"),
+            ));
+            bss
+        };
+
+        // ESS Privacy, without a WPA or RSN element
+        assert_eq!("WEP", parse_bss(&bss(0x0011)).unwrap().security);
+        // ESS only
+        assert_eq!("Open", parse_bss(&bss(0x0001)).unwrap().security);
+    }
+
+    #[test]
+    fn should_parse_wpa_vendor_element() {
+        // WPA: OUI type, version 1, group TKIP, 1 pairwise (TKIP), 1 AKM (PSK)
+        let wpa = [
+            0x00, 0x50, 0xf2, 1, 1, 0, 0x00, 0x50, 0xf2, 2, 1, 0, 0x00, 0x50, 0xf2, 2, 1, 0, 0x00,
+            0x50, 0xf2, 2,
+        ];
+        let mut elements = information_element(IE_SSID, b"legacy");
+        elements.extend(information_element(IE_VENDOR_SPECIFIC, &wpa));
+
+        assert_eq!(
+            (b"legacy".to_vec(), "PSK".to_string(), vec![]),
+            parse_information_elements(&elements)
+        );
+    }
+
+    #[test]
+    fn should_parse_messages() {
+        let mut buf = Vec::new();
+        let attributes = attribute(NL80211_ATTR_IFINDEX, &4u32.to_ne_bytes());
+        let len = (NLMSG_HEADER_LEN + GENL_HEADER_LEN + attributes.len()) as u32;
+        buf.extend_from_slice(&len.to_ne_bytes());
+        buf.extend_from_slice(&0x1cu16.to_ne_bytes());
+        buf.extend_from_slice(&0u16.to_ne_bytes());
+        buf.extend_from_slice(&7u32.to_ne_bytes());
+        buf.extend_from_slice(&0u32.to_ne_bytes());
+        buf.extend_from_slice(&[NL80211_CMD_NEW_SCAN_RESULTS, 1, 0, 0]);
+        buf.extend_from_slice(&attributes);
+        buf.extend_from_slice(&(NLMSG_HEADER_LEN as u32).to_ne_bytes());
+        buf.extend_from_slice(&NLMSG_DONE.to_ne_bytes());
+        buf.extend_from_slice(&0u16.to_ne_bytes());
+        buf.extend_from_slice(&7u32.to_ne_bytes());
+        buf.extend_from_slice(&0u32.to_ne_bytes());
+
+        let replies = parse_messages(&buf).unwrap();
+        assert_eq!(2, replies.len());
+        match replies[0] {
+            (7, Reply::Message(ref message)) => {
+                assert_eq!(NL80211_CMD_NEW_SCAN_RESULTS, message.command);
+                assert_eq!(
+                    Some(4),
+                    find_attribute(&message.attributes, NL80211_ATTR_IFINDEX).and_then(read_u32)
+                );
+            }
+            _ => panic!("expected a message"),
+        }
+        match replies[1] {
+            (7, Reply::Done) => (),
+            _ => panic!("expected done"),
+        }
+    }
+}
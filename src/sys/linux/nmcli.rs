@@ -0,0 +1,268 @@
+use std::collections::BTreeSet;
+use std::process::Command;
+use std::sync::atomic::AtomicBool;
+
+use crate::channel::channel_from_frequency;
+use crate::security::canonical_security;
+use crate::{
+    Error, FieldSet, Result, ScanOptions, ScanReport, ScanResult, ScanWarning, SignalBounds, Wifi,
+};
+
+/// Fields `scan` asks `nmcli` for, in the order they're parsed. `rate` is left out
+/// without `FieldSet::MAX_RATE`.
+const SCAN_FIELDS: &str = "in-use,bssid,ssid,chan,freq,signal,security,rate";
+
+/// Lists the hotspots NetworkManager knows about with `nmcli -t -f <fields> device wifi
+/// list`, which scans first when NetworkManager's results are older than 30 seconds.
+/// Warns about the lines of `nmcli`'s output that were skipped.
+pub(crate) fn scan_report(options: &ScanOptions, cancel: &AtomicBool) -> Result<ScanReport> {
+    let output = crate::sys::output(&mut scan_command(options.fields), cancel)?;
+    // e.g. NetworkManager isn't running
+    if !output.status.success() {
+        return Err(Error::CommandFailed(
+            output.status,
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    let (wifis, skipped) = parse_wifi_list(
+        &crate::sys::decode(&output.stdout, options.strict_utf8)?,
+        scan_fields(options.fields),
+        options.signal_bounds,
+    );
+
+    let mut report = ScanReport::from(ScanResult::fresh(wifis));
+    if skipped > 0 {
+        report
+            .warnings
+            .push(ScanWarning::SkippedLines { lines: skipped });
+    }
+    Ok(report)
+}
+
+pub(crate) fn scan_command(fields: FieldSet) -> Command {
+    let mut command = Command::new("nmcli");
+    command.args(["-t", "-f", scan_fields(fields), "device", "wifi", "list"]);
+    command
+}
+
+/// Returns the `SCAN_FIELDS` to ask for.
+fn scan_fields(fields: FieldSet) -> &'static str {
+    if fields.contains(FieldSet::MAX_RATE) {
+        SCAN_FIELDS
+    } else {
+        SCAN_FIELDS.trim_end_matches(",rate")
+    }
+}
+
+/// Parses the `scan_fields` of each hotspot, separated by `:`. `nmcli` escapes `:` and
+/// `\` in values with a `\`, so the mac addresses are escaped too.
+///
+/// Lines without the expected fields, or with a frequency or signal that isn't a
+/// number, are skipped, and returned as the number of skipped lines.
+fn parse_wifi_list(wifi_list: &str, scan_fields: &str, bounds: SignalBounds) -> (Vec<Wifi>, usize) {
+    let mut wifis = Vec::new();
+    let mut skipped = 0;
+
+    for line in wifi_list.lines().filter(|line| !line.is_empty()) {
+        let fields = split_fields(line);
+        let (frequency, percent) = match (fields.len() == scan_fields.split(',').count())
+            .then(|| (number(&fields[4]), fields[5].parse::<u8>().ok()))
+        {
+            Some((Some(frequency), Some(percent))) => (frequency, percent),
+            _ => {
+                skipped += 1;
+                continue;
+            }
+        };
+        let (security, akm_suites) = parse_security(&fields[6]);
+        wifis.push(Wifi {
+            mac: fields[1].to_lowercase(),
+            ssid: fields[2].clone(),
+            ssid_raw: fields[2].as_bytes().to_vec(),
+            channel: channel_from_frequency(frequency)
+                .map_or_else(|| fields[3].clone(), |channel| channel.to_string()),
+            signal_level: bounds.to_dbm(percent).to_string(),
+            security: canonical_security(&security),
+            frequency: Some(frequency),
+            akm_suites,
+            max_rate_mbps: fields.get(7).and_then(|rate| number(rate)),
+            // `*` for the hotspot NetworkManager is connected to, a space otherwise
+            connected: fields[0] == "*",
+            ..Default::default()
+        });
+    }
+
+    (wifis, skipped)
+}
+
+/// Splits a line of `nmcli`'s terse output at the `:`s that aren't escaped.
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => fields.last_mut().unwrap().extend(chars.next()),
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// Parses the number a value like `2437 MHz` or `270 Mbit/s` starts with.
+fn number(value: &str) -> Option<u32> {
+    value.split_whitespace().next()?.parse().ok()
+}
+
+/// Converts `nmcli`'s security, e.g. `WPA1 WPA2 802.1X`, to the security string `iw`
+/// would report, e.g. `WPA(IEEE 802.1X) WPA2(IEEE 802.1X)`, along with the suites of
+/// the RSN element. Open networks have no security, shown as `--` outside terse mode.
+fn parse_security(security: &str) -> (String, Vec<String>) {
+    let words: Vec<&str> = security.split_whitespace().collect();
+    let enterprise = words.contains(&"802.1X");
+
+    let mut versions = Vec::new();
+    let mut akm_suites = Vec::new();
+    for &word in &words {
+        let (version, suite) = match word {
+            "WEP" => {
+                versions.push(word.to_string());
+                continue;
+            }
+            "WPA1" => ("WPA", "PSK"),
+            "WPA2" => ("WPA2", "PSK"),
+            "WPA3" => ("WPA3", "SAE"),
+            _ => continue,
+        };
+        let suite = if enterprise { "IEEE 802.1X" } else { suite };
+        versions.push(format!("{}({})", version, suite));
+        // WPA2 and WPA3 share the RSN element
+        if version != "WPA" && !akm_suites.iter().any(|known| known == suite) {
+            akm_suites.push(suite.to_string());
+        }
+    }
+
+    (versions.join(" "), akm_suites)
+}
+
+/// Lists the names of the WiFi connection profiles saved in NetworkManager with
+/// `nmcli -t -f name,type connection show`.
+pub(crate) fn known_networks() -> Result<Vec<String>> {
+    let output = crate::sys::output(
+        Command::new("nmcli").args(["-t", "-f", "name,type", "connection", "show"]),
+        &AtomicBool::new(false),
+    )?;
+    // e.g. NetworkManager isn't running
+    if !output.status.success() {
+        return Err(Error::CommandFailed(
+            output.status,
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    Ok(parse_connections(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses `name:type` lines, keeping the names of `802-11-wireless` connections sorted
+/// and without duplicates. `nmcli` escapes `:` and `\` in names with a `\`.
+fn parse_connections(connections: &str) -> Vec<String> {
+    connections
+        .lines()
+        .filter_map(|line| line.rsplit_once(':'))
+        .filter(|(_, kind)| *kind == "802-11-wireless")
+        .map(|(name, _)| unescape(name))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn unescape(name: &str) -> String {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn should_parse_wifi_list() {
+        let fixture = fs::read_to_string("tests/fixtures/nmcli/device_wifi_list_01.txt").unwrap();
+
+        let (result, skipped) = parse_wifi_list(&fixture, SCAN_FIELDS, SignalBounds::default());
+        assert_eq!(0, skipped);
+        assert_eq!(5, result.len());
+        assert_eq!(
+            Wifi {
+                mac: "00:11:22:33:44:55".to_string(),
+                ssid: "home".to_string(),
+                ssid_raw: b"home".to_vec(),
+                channel: "6".to_string(),
+                signal_level: SignalBounds::default().to_dbm(75).to_string(),
+                security: "WPA2-Personal".to_string(),
+                frequency: Some(2437),
+                akm_suites: vec!["PSK".to_string()],
+                max_rate_mbps: Some(270),
+                connected: true,
+                ..Default::default()
+            },
+            result[0]
+        );
+        assert_eq!("66:77:88:99:aa:bb", result[1].mac);
+        assert!(!result[1].connected);
+        assert_eq!("cafe: guest", result[1].ssid);
+        assert_eq!("Open", result[1].security);
+        assert_eq!("WPA/WPA2-Enterprise", result[2].security);
+        assert_eq!("WPA3-Personal", result[3].security);
+        assert!(result[3].is_wpa3_transition());
+        assert_eq!(Some(1201), result[3].max_rate_mbps);
+        assert_eq!("WEP", result[4].security);
+        assert!(result[4].is_hidden());
+    }
+
+    #[test]
+    fn should_skip_malformed_lines() {
+        let list = "*:00\\:11\\:22\\:33\\:44\\:55:home:6:2437 MHz:75:WPA2:270 Mbit/s\n\
+                    garbage\n\
+                    \x20:66\\:77\\:88\\:99\\:AA\\:BB:cafe:36:unknown:52::540 Mbit/s\n\
+                    \x20:AA\\:BB\\:CC\\:DD\\:EE\\:01:office:11:2462 MHz:strong:WPA2:130 Mbit/s\n\
+                    \x20:AA\\:BB\\:CC\\:DD\\:EE\\:02:flat:149:5745 MHz:90:WPA2 WPA3:1201 Mbit/s\n";
+
+        let (result, skipped) = parse_wifi_list(list, SCAN_FIELDS, SignalBounds::default());
+        assert_eq!(3, skipped);
+        let ssids: Vec<_> = result.iter().map(|wifi| wifi.ssid.as_str()).collect();
+        assert_eq!(vec!["home", "flat"], ssids);
+    }
+
+    #[test]
+    fn should_leave_out_rate_field() {
+        assert_eq!(SCAN_FIELDS, scan_fields(FieldSet::all()));
+        let fields = scan_fields(FieldSet::empty());
+        assert_eq!("in-use,bssid,ssid,chan,freq,signal,security", fields);
+
+        let (result, _) = parse_wifi_list(
+            " :00\\:11\\:22\\:33\\:44\\:55:home:6:2437 MHz:75:WPA2\n",
+            fields,
+            SignalBounds::default(),
+        );
+        assert_eq!("home", result[0].ssid);
+        assert_eq!(None, result[0].max_rate_mbps);
+    }
+
+    #[test]
+    fn should_parse_connections() {
+        let fixture = fs::read_to_string("tests/fixtures/nmcli/connection_show_01.txt").unwrap();
+
+        assert_eq!(
+            vec!["back\\slash", "cafe: guest", "home"],
+            parse_connections(&fixture)
+        );
+    }
+}
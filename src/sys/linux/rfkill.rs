@@ -0,0 +1,70 @@
+use std::sync::atomic::AtomicBool;
+
+use super::sbin_command;
+use crate::{Error, RadioStatus, Result};
+
+/// Returns whether the WiFi radios are blocked with `rfkill list wifi`.
+pub(crate) fn radio_status() -> Result<RadioStatus> {
+    let output = crate::sys::output(
+        sbin_command("rfkill").args(["list", "wifi"]),
+        &AtomicBool::new(false),
+    )?;
+    if !output.status.success() {
+        return Err(Error::CommandFailed(
+            output.status,
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    Ok(parse_rfkill_list(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses the `Soft blocked` and `Hard blocked` lines listed for each radio, returning
+/// the status of the least blocked one.
+fn parse_rfkill_list(list: &str) -> RadioStatus {
+    let mut radios = Vec::new();
+    for line in list.lines() {
+        // every radio starts with an unindented `1: phy0: Wireless LAN` line
+        if !line.starts_with(char::is_whitespace) && !line.is_empty() {
+            radios.push(RadioStatus::Unblocked);
+        }
+        let line = line.trim();
+        let radio = match radios.last_mut() {
+            Some(radio) => radio,
+            None => continue,
+        };
+        // a hard block wins over a soft one, as unblocking in software won't help
+        if line == "Hard blocked: yes" {
+            *radio = RadioStatus::HardBlocked;
+        } else if line == "Soft blocked: yes" && *radio == RadioStatus::Unblocked {
+            *radio = RadioStatus::SoftBlocked;
+        }
+    }
+
+    [
+        RadioStatus::Unblocked,
+        RadioStatus::SoftBlocked,
+        RadioStatus::HardBlocked,
+    ]
+    .iter()
+    .copied()
+    .find(|status| radios.contains(status))
+    .unwrap_or(RadioStatus::NoRadio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn should_parse_rfkill_list() {
+        let fixture = fs::read_to_string("tests/fixtures/rfkill/rfkill_list_wifi_01.txt").unwrap();
+        assert_eq!(RadioStatus::SoftBlocked, parse_rfkill_list(&fixture));
+
+        let unblocked = "0: phy0: Wireless LAN\n\tSoft blocked: no\n\tHard blocked: no\n";
+        assert_eq!(RadioStatus::Unblocked, parse_rfkill_list(unblocked));
+        let hard_blocked = "0: phy0: Wireless LAN\n\tSoft blocked: no\n\tHard blocked: yes\n";
+        assert_eq!(RadioStatus::HardBlocked, parse_rfkill_list(hard_blocked));
+        assert_eq!(RadioStatus::NoRadio, parse_rfkill_list(""));
+    }
+}
@@ -0,0 +1,130 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{sbin_command, set_ssid};
+use crate::channel::channel_from_frequency;
+use crate::security::{canonical_security, parse_supplicant_flags};
+use crate::{Error, Result, ScanOptions, ScanReport, ScanResult, ScanWarning, Wifi};
+
+/// How long wpa_supplicant is given to finish the scan `wpa_cli scan` triggers.
+const SCAN_WAIT: Duration = Duration::from_secs(3);
+
+/// Triggers a scan with `wpa_cli scan`, then lists its results once wpa_supplicant has
+/// had `SCAN_WAIT` to finish it. Warns about the lines of `wpa_cli`'s output that were
+/// skipped.
+pub(crate) fn scan_report(options: &ScanOptions, cancel: &AtomicBool) -> Result<ScanReport> {
+    run(options, "scan", cancel)?;
+
+    let start = Instant::now();
+    while start.elapsed() < SCAN_WAIT {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(Error::Cancelled);
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    let (wifis, skipped) = parse_scan_results(&run(options, "scan_results", cancel)?);
+    let mut report = ScanReport::from(ScanResult::fresh(wifis));
+    if skipped > 0 {
+        report
+            .warnings
+            .push(ScanWarning::SkippedLines { lines: skipped });
+    }
+    Ok(report)
+}
+
+/// Lists the results of wpa_supplicant's last scan with `wpa_cli scan_results`.
+pub(crate) fn scan_cached(options: &ScanOptions, cancel: &AtomicBool) -> Result<Vec<Wifi>> {
+    Ok(parse_scan_results(&run(options, "scan_results", cancel)?).0)
+}
+
+fn run(options: &ScanOptions, command: &str, cancel: &AtomicBool) -> Result<String> {
+    let output = crate::sys::output(sbin_command("wpa_cli").arg(command), cancel)?;
+    // e.g. wpa_supplicant isn't running
+    if !output.status.success() {
+        return Err(Error::CommandFailed(
+            output.status,
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        ));
+    }
+    Ok(crate::sys::decode(&output.stdout, options.strict_utf8)?.into_owned())
+}
+
+/// Parses the tab separated `bssid / frequency / signal level / flags / ssid` lines,
+/// skipping the `Selected interface` and header lines. Lines with an invalid frequency
+/// are skipped too, and counted.
+fn parse_scan_results(scan_results: &str) -> (Vec<Wifi>, usize) {
+    let mut wifis = Vec::new();
+    let mut skipped = 0;
+
+    for line in scan_results.lines() {
+        let fields: Vec<&str> = line.splitn(5, '\t').collect();
+        if fields.len() < 4 {
+            continue;
+        }
+
+        let frequency = match fields[1].trim().parse() {
+            Ok(frequency) => frequency,
+            Err(_) => {
+                skipped += 1;
+                continue;
+            }
+        };
+        let (security, akm_suites) = parse_supplicant_flags(fields[3]);
+        let mut wifi = Wifi {
+            mac: fields[0].to_string(),
+            channel: channel_from_frequency(frequency)
+                .map(|channel| channel.to_string())
+                .unwrap_or_default(),
+            signal_level: fields[2].trim().to_string(),
+            security: canonical_security(&security),
+            frequency: Some(frequency),
+            akm_suites,
+            ..Default::default()
+        };
+        // hidden networks have an empty SSID, which may have lost its tab
+        set_ssid(&mut wifi, fields.get(4).copied().unwrap_or_default());
+        wifis.push(wifi);
+    }
+
+    (wifis, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn should_parse_scan_results() {
+        let fixture = fs::read_to_string("tests/fixtures/wpa_cli/scan_results_01.txt").unwrap();
+
+        let (result, skipped) = parse_scan_results(&fixture);
+        assert_eq!(5, result.len());
+        // the line with an invalid frequency
+        assert_eq!(1, skipped);
+        assert_eq!(
+            Wifi {
+                mac: "00:11:22:33:44:55".to_string(),
+                ssid: "hello".to_string(),
+                ssid_raw: b"hello".to_vec(),
+                channel: "1".to_string(),
+                signal_level: "-45".to_string(),
+                security: "WPA2-Personal".to_string(),
+                frequency: Some(2412),
+                akm_suites: vec!["PSK".to_string()],
+                ..Default::default()
+            },
+            result[0]
+        );
+        assert_eq!("WPA/WPA2-Personal", result[1].security);
+        assert_eq!("36", result[1].channel);
+        assert_eq!("WPA3-Personal", result[2].security);
+        assert!(result[2].is_wpa3_transition());
+        assert_eq!("WPA2-Enterprise", result[3].security);
+        assert_eq!(vec!["IEEE 802.1X"], result[3].akm_suites);
+        assert_eq!("Open", result[4].security);
+        assert!(result[4].is_hidden());
+    }
+}
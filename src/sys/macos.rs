@@ -1,19 +1,166 @@
-use crate::{Error, Result, Wifi};
+use crate::backend::{probe, Tool};
+use crate::channel::{frequency_from_channel, Band};
+use crate::link::parse_bitrate;
+use crate::security::canonical_security;
+use crate::{
+    BackendStatus, Error, Interface, LinkInfo, Result, ScanOptions, ScanReport, ScanResult, Wifi,
+};
+use std::process::Command;
+use std::sync::atomic::AtomicBool;
+
+const AIRPORT_PATH: &str = "/System/Library/PrivateFrameworks/Apple80211.\
+                            framework/Versions/Current/Resources/airport";
 
 /// Returns a list of WiFi hotspots in your area - (OSX/MacOS) uses `airport`
-pub(crate) fn scan() -> Result<Vec<Wifi>> {
-    use std::process::Command;
-    let output = Command::new(
-        "/System/Library/PrivateFrameworks/Apple80211.\
-         framework/Versions/Current/Resources/airport",
-    )
-    .arg("-s")
-    .output()
-    .map_err(|_| Error::CommandNotFound)?;
+pub(crate) fn scan(options: &ScanOptions, cancel: &AtomicBool) -> Result<ScanResult> {
+    scan_report(options, cancel).map(ScanReport::into_result)
+}
+
+/// Same as `scan`, warning when `airport`'s output has hotspots but none could be parsed.
+pub(crate) fn scan_report(options: &ScanOptions, cancel: &AtomicBool) -> Result<ScanReport> {
+    let output = super::output(&mut scan_command(), cancel)?;
+
+    let data = super::decode(&output.stdout, options.strict_utf8)?;
+
+    let wifis = parse_airport(&data)?;
+    Ok(ScanReport::checked(
+        ScanResult::fresh(wifis),
+        &data,
+        is_airport_hotspot_line,
+    ))
+}
+
+fn is_airport_hotspot_line(line: &str) -> bool {
+    // everything but the `SSID BSSID RSSI ...` header
+    !line.trim().is_empty() && !line.trim_start().starts_with("SSID BSSID")
+}
+
+fn scan_command() -> Command {
+    let mut command = Command::new(AIRPORT_PATH);
+    command.arg("-s");
+    command
+}
+
+/// Lists the commands `scan` runs - (OSX/MacOS) `airport`
+pub(crate) fn scan_commands(_options: &ScanOptions) -> Vec<String> {
+    vec![super::describe(&scan_command())]
+}
+
+/// `airport` can't return the results of the last scan, so this always scans.
+pub(crate) fn scan_cached(options: &ScanOptions) -> Result<ScanResult> {
+    scan(options, &AtomicBool::new(false))
+}
+
+/// Returns the hotspot you are currently connected to - (OSX/MacOS) uses `airport`, or
+/// `wdutil info` on versions of macOS where `airport` was removed, which needs root
+pub(crate) fn connected_network() -> Result<Option<Wifi>> {
+    match airport_info() {
+        Err(Error::CommandNotFound) => {}
+        // macOS 14.4 left a stub that only prints a deprecation warning
+        Ok(info) if info.contains("airport command line tool is deprecated") => {
+            if let Some(wifi) = parse_airport_info(&info)? {
+                return Ok(Some(wifi));
+            }
+        }
+        info => return parse_airport_info(&info?),
+    }
+    parse_wdutil_info(&wdutil_info()?)
+}
 
-    let data = String::from_utf8_lossy(&output.stdout);
+/// Returns the current connection's details - (OSX/MacOS) uses `airport`
+pub(crate) fn connected_link_info() -> Result<Option<LinkInfo>> {
+    parse_airport_link_info(&airport_info()?)
+}
+
+/// Probes the tools used to scan - (OSX/MacOS) `airport`, which doesn't report a version
+pub(crate) fn check_backend() -> Result<BackendStatus> {
+    let tool = Tool {
+        version: None,
+        ..probe("airport", Command::new(AIRPORT_PATH).arg("-h"))
+    };
+    Ok(BackendStatus { tools: vec![tool] })
+}
+
+/// Returns true if there's a WiFi hardware port - (OSX/MacOS) uses `networksetup`
+pub(crate) fn has_wifi_hardware() -> bool {
+    wifi_device().is_ok_and(|device| device.is_some())
+}
+
+/// Lists the WiFi interfaces - (OSX/MacOS) uses `networksetup` to find the interface
+/// and `airport` for its connection. `airport` only reports one interface
+pub(crate) fn show_interfaces() -> Result<Vec<Interface>> {
+    let name = match wifi_device()? {
+        Some(name) => name,
+        None => return Ok(vec![]),
+    };
+    let network = parse_airport_info(&airport_info()?)?;
 
-    parse_airport(&data)
+    Ok(vec![Interface {
+        name,
+        connected: network.is_some(),
+        ssid: network.as_ref().map(|network| network.ssid.clone()),
+        bssid: network.map(|network| network.mac),
+        ..Default::default()
+    }])
+}
+
+fn wifi_device() -> Result<Option<String>> {
+    let output = Command::new("networksetup")
+        .arg("-listallhardwareports")
+        .output()
+        .map_err(|_| Error::CommandNotFound)?;
+
+    Ok(parse_hardware_ports(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Returns the device of the `Wi-Fi` hardware port (called `AirPort` on older versions
+/// of macOS), e.g. `en0`.
+fn parse_hardware_ports(ports: &str) -> Option<String> {
+    let mut lines = ports.lines();
+    lines.find(|line| {
+        line.strip_prefix("Hardware Port: ")
+            .is_some_and(|port| port.trim() == "Wi-Fi" || port.trim() == "AirPort")
+    })?;
+    lines
+        .next()?
+        .strip_prefix("Device: ")
+        .map(|device| device.trim().to_string())
+}
+
+fn airport_info() -> Result<String> {
+    let output = Command::new(AIRPORT_PATH)
+        .arg("-I")
+        .output()
+        .map_err(|_| Error::CommandNotFound)?;
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Fails with `Error::NeedsRoot` when not run as root.
+fn wdutil_info() -> Result<String> {
+    let output = Command::new("wdutil")
+        .arg("info")
+        .output()
+        .map_err(|_| Error::CommandNotFound)?;
+    if !output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if needs_root(&stdout) || needs_root(&stderr) {
+            return Err(Error::NeedsRoot("wdutil"));
+        }
+        return Err(Error::CommandFailed(output.status, stderr));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Returns true if `wdutil`'s error asks to be run as root, e.g. `usage: sudo wdutil
+/// <command>`.
+fn needs_root(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("sudo") || message.contains("root")
 }
 
 fn parse_airport(network_list: &str) -> Result<Vec<Wifi>> {
@@ -50,15 +197,140 @@ fn parse_airport(network_list: &str) -> Result<Vec<Wifi>> {
         wifis.push(Wifi {
             mac: mac.to_string(),
             ssid: ssid.to_string(),
+            ssid_raw: ssid.as_bytes().to_vec(),
             channel: channel.to_string(),
             signal_level: signal_level.to_string(),
-            security: security.to_string(),
+            security: canonical_security(security),
+            ..Default::default()
         });
     }
 
     Ok(wifis)
 }
 
+fn parse_airport_info(info: &str) -> Result<Option<Wifi>> {
+    let mut wifi = Wifi::default();
+    let mut noise = None;
+    for line in info.lines() {
+        let line = line.trim_start();
+        let index = match line.find(": ") {
+            Some(index) => index,
+            None => continue,
+        };
+        let (key, value) = (&line[..index], &line[index + 2..]);
+
+        match key {
+            "BSSID" => wifi.mac = value.trim().to_string(),
+            "SSID" => {
+                wifi.ssid = value.to_string();
+                wifi.ssid_raw = value.as_bytes().to_vec();
+            }
+            "agrCtlRSSI" => wifi.signal_level = value.trim().to_string(),
+            "agrCtlNoise" => noise = value.trim().parse::<i32>().ok(),
+            // e.g. `112,1` where the second number is the channel width
+            "channel" => wifi.channel = value.split(',').next().unwrap_or("").trim().to_string(),
+            "link auth" => wifi.security = canonical_security(value),
+            _ => (),
+        }
+    }
+
+    wifi.snr_db = match (wifi.signal_dbm(), noise) {
+        (Some(signal), Some(noise)) => Some(signal - noise),
+        _ => None,
+    };
+
+    // `airport` omits the BSSID when it isn't associated
+    if wifi.mac.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(wifi))
+    }
+}
+
+/// Parses the `WIFI` section of `wdutil info`, which lists the connection's details as
+/// `SSID                 : OurTest` lines. Other sections, e.g. `BLUETOOTH`, use the
+/// same layout.
+fn parse_wdutil_info(info: &str) -> Result<Option<Wifi>> {
+    let mut wifi = Wifi::default();
+    let mut noise = None;
+    let mut section = "";
+    for line in info.lines() {
+        let line = line.trim();
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            // the section names are framed by lines of dashes
+            None => {
+                if line.chars().any(char::is_alphabetic) {
+                    section = line;
+                }
+                continue;
+            }
+        };
+        if section != "WIFI" {
+            continue;
+        }
+
+        match key {
+            // `None` when not associated
+            "BSSID" if value != "None" => wifi.mac = value.to_string(),
+            "SSID" if value != "None" => {
+                wifi.ssid = value.to_string();
+                wifi.ssid_raw = value.as_bytes().to_vec();
+            }
+            "RSSI" => wifi.signal_level = value.trim_end_matches(" dBm").to_string(),
+            "Noise" => noise = value.trim_end_matches(" dBm").parse::<i32>().ok(),
+            // e.g. `5g112/80`, the band, the channel and its width
+            "Channel" => {
+                let band = match value.get(..2) {
+                    Some("2g") => Some(Band::TwoPointFourGhz),
+                    Some("5g") => Some(Band::FiveGhz),
+                    Some("6g") => Some(Band::SixGhz),
+                    _ => None,
+                };
+                let number = value
+                    .get(2..)
+                    .and_then(|channel| channel.split('/').next())
+                    .unwrap_or("");
+                wifi.channel = number.to_string();
+                wifi.frequency = band
+                    .zip(number.parse().ok())
+                    .and_then(|(band, number)| frequency_from_channel(band, number));
+            }
+            "Security" => wifi.security = canonical_security(value),
+            _ => (),
+        }
+    }
+
+    wifi.snr_db = match (wifi.signal_dbm(), noise) {
+        (Some(signal), Some(noise)) => Some(signal - noise),
+        _ => None,
+    };
+
+    if wifi.mac.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(wifi))
+    }
+}
+
+/// `airport` only reports the transmit rate
+fn parse_airport_link_info(info: &str) -> Result<Option<LinkInfo>> {
+    let network = match parse_airport_info(info)? {
+        Some(network) => network,
+        None => return Ok(None),
+    };
+    let tx_bitrate_mbps = info
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("lastTxRate: "))
+        .find_map(parse_bitrate);
+
+    Ok(Some(LinkInfo {
+        network,
+        tx_bitrate_mbps,
+        rx_bitrate_mbps: None,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,17 +344,21 @@ mod tests {
         expected.push(Wifi {
             mac: "00:35:1a:90:56:03".to_string(),
             ssid: "OurTest".to_string(),
+            ssid_raw: b"OurTest".to_vec(),
             channel: "112".to_string(),
             signal_level: "-70".to_string(),
-            security: "WPA2(PSK/AES/AES)".to_string(),
+            security: "WPA2-Personal".to_string(),
+            ..Default::default()
         });
 
         expected.push(Wifi {
             mac: "00:35:1a:90:56:00".to_string(),
             ssid: "TEST-Wifi".to_string(),
+            ssid_raw: b"TEST-Wifi".to_vec(),
             channel: "1".to_string(),
             signal_level: "-67".to_string(),
-            security: "WPA2(PSK/AES/AES)".to_string(),
+            security: "WPA2-Personal".to_string(),
+            ..Default::default()
         });
 
         let path = PathBuf::from("tests/fixtures/airport/airport01.txt");
@@ -113,4 +389,93 @@ mod tests {
             Error::HeaderNotFound("BSSID")
         );
     }
+
+    #[test]
+    fn should_parse_airport_info() {
+        let expected = Wifi {
+            mac: "0:35:1a:90:56:3".to_string(),
+            ssid: "OurTest".to_string(),
+            ssid_raw: b"OurTest".to_vec(),
+            channel: "112".to_string(),
+            signal_level: "-55".to_string(),
+            security: "WPA2-Personal".to_string(),
+            snr_db: Some(34),
+            ..Default::default()
+        };
+
+        let path = PathBuf::from("tests/fixtures/airport/airport_info01.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        assert_eq!(Some(expected), parse_airport_info(&filestr).unwrap());
+    }
+
+    #[test]
+    fn should_parse_airport_link_info() {
+        let path = PathBuf::from("tests/fixtures/airport/airport_info01.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        let info = parse_airport_link_info(&filestr).unwrap().unwrap();
+        assert_eq!("OurTest", info.network.ssid);
+        assert_eq!(Some(173.0), info.tx_bitrate_mbps);
+        assert_eq!(None, info.rx_bitrate_mbps);
+    }
+
+    #[test]
+    fn should_parse_airport_info_not_connected() {
+        assert_eq!(None, parse_airport_info("AirPort: Off\n").unwrap());
+    }
+
+    #[test]
+    fn should_parse_wdutil_info() {
+        let expected = Wifi {
+            mac: "00:35:1a:90:56:03".to_string(),
+            ssid: "OurTest".to_string(),
+            ssid_raw: b"OurTest".to_vec(),
+            channel: "112".to_string(),
+            signal_level: "-55".to_string(),
+            security: "WPA2-Personal".to_string(),
+            frequency: Some(5560),
+            snr_db: Some(34),
+            ..Default::default()
+        };
+
+        let path = PathBuf::from("tests/fixtures/wdutil/wdutil_info_01.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        assert_eq!(Some(expected), parse_wdutil_info(&filestr).unwrap());
+    }
+
+    #[test]
+    fn should_parse_wdutil_info_not_connected() {
+        let info = "WIFI\n    Power                : On [On]\n    SSID                 : None\n    BSSID                : None\n";
+        assert_eq!(None, parse_wdutil_info(info).unwrap());
+    }
+
+    #[test]
+    fn should_detect_wdutil_needing_root() {
+        assert!(needs_root("usage: sudo wdutil <command>"));
+        assert!(!needs_root("wdutil: unknown command"));
+    }
+
+    #[test]
+    fn should_find_wifi_hardware_port() {
+        let path = PathBuf::from("tests/fixtures/networksetup/listallhardwareports01.txt");
+        let mut file = File::open(&path).unwrap();
+        let mut filestr = String::new();
+        file.read_to_string(&mut filestr).unwrap();
+
+        assert_eq!(Some("en1".to_string()), parse_hardware_ports(&filestr));
+        assert_eq!(
+            None,
+            parse_hardware_ports(
+                "Hardware Port: Ethernet\nDevice: en0\nEthernet Address: 00:11:22:33:44:55\n"
+            )
+        );
+    }
 }
@@ -1,66 +1,311 @@
 use regex::Regex;
 
-use crate::{Error, Result, Wifi};
+use crate::backend::{probe, Tool};
+use crate::channel::{frequency_from_channel, Band};
+use crate::link::parse_bitrate;
+use crate::security::canonical_security;
+use crate::{
+    BackendStatus, Error, Interface, LinkInfo, Result, ScanOptions, ScanReport, ScanResult,
+    SignalBounds, Wifi,
+};
+use std::process::Command;
+use std::sync::atomic::AtomicBool;
 
-/// Returns a list of WiFi hotspots in your area - (Windows) uses `netsh`
-pub fn scan() -> Result<Vec<Wifi>> {
-    use std::process::Command;
+/// Returns a list of WiFi hotspots in your area - (Windows) uses `netsh`, which lists
+/// the results of Windows' own background scans, so they're never fresh.
+pub fn scan(options: &ScanOptions, cancel: &AtomicBool) -> Result<ScanResult> {
+    scan_report(options, cancel).map(ScanReport::into_result)
+}
+
+/// Same as `scan`, warning when `netsh`'s output has hotspots but none could be parsed.
+pub fn scan_report(options: &ScanOptions, cancel: &AtomicBool) -> Result<ScanReport> {
+    let output = super::output(&mut scan_command(), cancel)?;
+
+    let data = super::decode(&output.stdout, options.strict_utf8)?;
+
+    let wifis = parse_netsh(&data, options.signal_bounds)?;
+    Ok(ScanReport::checked(
+        ScanResult::cached(wifis),
+        &data,
+        is_netsh_hotspot_line,
+    ))
+}
+
+fn is_netsh_hotspot_line(line: &str) -> bool {
+    // `SSID 1 : name`, the other lines are either details or say how many there are
+    line.starts_with("SSID ")
+}
+
+fn scan_command() -> Command {
+    let mut command = Command::new("netsh.exe");
+    command.args(["wlan", "show", "networks", "mode=Bssid"]);
+    command
+}
+
+/// Lists the commands `scan` runs - (Windows) `netsh`
+pub fn scan_commands(_options: &ScanOptions) -> Vec<String> {
+    vec![super::describe(&scan_command())]
+}
+
+/// `netsh` already lists the results of Windows' own background scans rather than
+/// scanning, so this is the same as `scan`.
+pub fn scan_cached(options: &ScanOptions) -> Result<ScanResult> {
+    scan(options, &AtomicBool::new(false))
+}
+
+/// Returns the hotspot you are currently connected to - (Windows) uses `netsh`
+pub fn connected_network() -> Result<Option<Wifi>> {
+    parse_netsh_interfaces(&netsh_interfaces()?)
+}
+
+/// Returns the current connection's details - (Windows) uses `netsh`
+pub fn connected_link_info() -> Result<Option<LinkInfo>> {
+    parse_netsh_link_info(&netsh_interfaces()?)
+}
+
+/// Probes the tools used to scan - (Windows) `netsh`, which doesn't report a version
+pub fn check_backend() -> Result<BackendStatus> {
+    let tool = Tool {
+        version: None,
+        ..probe("netsh", Command::new("netsh.exe").arg("/?"))
+    };
+    Ok(BackendStatus { tools: vec![tool] })
+}
+
+/// Returns true if there's a wireless interface - (Windows) uses `netsh`
+pub fn has_wifi_hardware() -> bool {
+    netsh_interfaces().is_ok_and(|interfaces| parse_netsh_interface_count(&interfaces) > 0)
+}
+
+/// Lists the WiFi interfaces - (Windows) uses `netsh`
+pub fn show_interfaces() -> Result<Vec<Interface>> {
+    Ok(parse_netsh_interface_list(&netsh_interfaces()?))
+}
+
+fn netsh_interfaces() -> Result<String> {
     let output = Command::new("netsh.exe")
-        .args(&["wlan", "show", "networks", "mode=Bssid"])
+        .args(["wlan", "show", "interfaces"])
         .output()
         .map_err(|_| Error::CommandNotFound)?;
 
-    let data = String::from_utf8_lossy(&output.stdout);
-
-    parse_netsh(&data)
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
-fn parse_netsh(network_list: &str) -> Result<Vec<Wifi>> {
+fn parse_netsh(network_list: &str, bounds: SignalBounds) -> Result<Vec<Wifi>> {
     let mut wifis = Vec::new();
 
     // Regex for matching split, SSID and MAC, since these aren't pulled directly
     let split_regex = Regex::new("\nSSID").map_err(|_| Error::SyntaxRegexError)?;
-    let ssid_regex = Regex::new("^ [0-9]* : ").map_err(|_| Error::SyntaxRegexError)?;
+    // hidden networks have an empty name, which may have lost its trailing space
+    let ssid_regex = Regex::new("^ [0-9]+ :( |$)").map_err(|_| Error::SyntaxRegexError)?;
     let mac_regex = Regex::new("[a-fA-F0-9:]{17}").map_err(|_| Error::SyntaxRegexError)?;
 
-    for block in split_regex.split(network_list) {
-        let mut wifi_macs = Vec::new();
+    // the first block is the header naming the interface, which can contain any of the
+    // words matched below
+    for block in split_regex.split(network_list).skip(1) {
         let mut wifi_ssid = String::new();
-        let mut wifi_channels = Vec::new();
-        let mut wifi_rssi = Vec::new();
         let mut wifi_security = String::new();
+        let mut wifi_encryption = None;
+        // each BSSID sub-block only sets the fields it lists, along with its band, which
+        // is only listed by newer versions of Windows
+        let mut bssids: Vec<(Wifi, Option<Band>)> = Vec::new();
 
         for line in block.lines() {
             if ssid_regex.is_match(line) {
-                wifi_ssid = line.split(":").nth(1).unwrap_or("").trim().to_string();
-            } else if line.find("Authentication").is_some() {
-                wifi_security = line.split(":").nth(1).unwrap_or("").trim().to_string();
-            } else if line.find("BSSID").is_some() {
+                // SSIDs can contain colons
+                wifi_ssid = line
+                    .split_once(':')
+                    .map_or("", |(_, ssid)| ssid)
+                    .trim()
+                    .to_string();
+                continue;
+            }
+            // keys are matched exactly, e.g. `Channel Utilization` in the `Bss Load` of
+            // newer versions of Windows isn't the channel
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value),
+                None => continue,
+            };
+            if key == "Authentication" {
+                wifi_security = value.trim().to_string();
+                continue;
+            } else if key == "Encryption" {
+                wifi_encryption = Some(value.trim().to_string()).filter(|value| !value.is_empty());
+                continue;
+            } else if key.starts_with("BSSID ") {
                 let captures = mac_regex.captures(line).ok_or(Error::SyntaxRegexError)?;
-                wifi_macs.push(captures.get(0).ok_or(Error::SyntaxRegexError)?);
-            } else if line.find("Signal").is_some() {
-                let percent = line.split(":").nth(1).unwrap_or("").trim().replace("%", "");
-                let percent: i32 = percent.parse().map_err(|_| Error::SyntaxRegexError)?;
-                wifi_rssi.push(percent / 2 - 100);
-            } else if line.find("Channel").is_some() {
-                wifi_channels.push(line.split(":").nth(1).unwrap_or("").trim().to_string());
+                let mac = captures.get(0).ok_or(Error::SyntaxRegexError)?;
+                let wifi = Wifi {
+                    mac: mac.as_str().to_string(),
+                    ..Default::default()
+                };
+                bssids.push((wifi, None));
+                continue;
+            }
+
+            let (wifi, band) = match bssids.last_mut() {
+                Some(bssid) => (&mut bssid.0, &mut bssid.1),
+                None => continue,
+            };
+            match key {
+                "Radio type" => wifi.radio_type = Some(value.trim().to_string()),
+                "Band" => *band = parse_band(value),
+                "Signal" => {
+                    wifi.signal_level = parse_signal_percent(line, value, bounds)?.to_string()
+                }
+                "Channel" => wifi.channel = value.trim().to_string(),
+                _ => (),
             }
         }
 
-        for (mac, channel, rssi) in izip!(wifi_macs, wifi_channels, wifi_rssi) {
-            wifis.push(Wifi {
-                mac: mac.as_str().to_string(),
-                ssid: wifi_ssid.to_string(),
-                channel: channel.to_string(),
-                signal_level: rssi.to_string(),
-                security: wifi_security.to_string(),
-            });
+        for (mut wifi, band) in bssids {
+            wifi.frequency = match (band, wifi.channel.parse()) {
+                (Some(band), Ok(number)) => frequency_from_channel(band, number),
+                _ => None,
+            };
+            wifi.ssid = wifi_ssid.clone();
+            wifi.ssid_raw = wifi_ssid.as_bytes().to_vec();
+            wifi.security = canonical_security(&wifi_security);
+            wifi.encryption = wifi_encryption.clone();
+            wifis.push(wifi);
         }
     }
 
     Ok(wifis)
 }
 
+/// Counts the interfaces, each of which starts with its `Name`. Without any wireless
+/// interfaces `netsh` prints an error instead, e.g. `There is no wireless interface on
+/// the system.`
+fn parse_netsh_interface_count(interface_list: &str) -> usize {
+    interface_list
+        .lines()
+        .filter(|line| {
+            line.find(':')
+                .is_some_and(|index| line[..index].trim() == "Name")
+        })
+        .count()
+}
+
+/// Parses every interface, each of which starts with its `Name`.
+fn parse_netsh_interface_list(interface_list: &str) -> Vec<Interface> {
+    let mut interfaces: Vec<Interface> = Vec::new();
+
+    for line in interface_list.lines() {
+        let index = match line.find(':') {
+            Some(index) => index,
+            None => continue,
+        };
+        let (key, value) = (line[..index].trim(), line[index + 1..].trim());
+
+        if key == "Name" {
+            interfaces.push(Interface {
+                name: value.to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let interface = match interfaces.last_mut() {
+            Some(interface) => interface,
+            None => continue,
+        };
+        match key {
+            "State" => interface.connected = value == "connected",
+            "SSID" => interface.ssid = Some(value.to_string()),
+            "BSSID" => interface.bssid = Some(value.to_string()),
+            "Radio type" => interface.radio_type = Some(value.to_string()),
+            _ => (),
+        }
+    }
+
+    interfaces
+}
+
+/// Parses a band, e.g. `2.4 GHz`.
+fn parse_band(band: &str) -> Option<Band> {
+    match band.trim() {
+        "2.4 GHz" => Some(Band::TwoPointFourGhz),
+        "5 GHz" => Some(Band::FiveGhz),
+        "6 GHz" => Some(Band::SixGhz),
+        _ => None,
+    }
+}
+
+fn parse_netsh_interfaces(interface_list: &str) -> Result<Option<Wifi>> {
+    let mut wifi = Wifi::default();
+    let mut connected = false;
+
+    for line in interface_list.lines() {
+        let index = match line.find(':') {
+            Some(index) => index,
+            None => continue,
+        };
+        let (key, value) = (line[..index].trim(), line[index + 1..].trim());
+
+        match key {
+            "State" => connected = value == "connected",
+            "SSID" => {
+                wifi.ssid = value.to_string();
+                wifi.ssid_raw = value.as_bytes().to_vec();
+            }
+            "BSSID" => wifi.mac = value.to_string(),
+            "Channel" => wifi.channel = value.to_string(),
+            "Authentication" => wifi.security = canonical_security(value),
+            "Radio type" => wifi.radio_type = Some(value.to_string()),
+            "Signal" => {
+                wifi.signal_level =
+                    parse_signal_percent(line, value, SignalBounds::default())?.to_string()
+            }
+            _ => (),
+        }
+    }
+
+    if connected && !wifi.mac.is_empty() {
+        Ok(Some(wifi))
+    } else {
+        Ok(None)
+    }
+}
+
+fn parse_netsh_link_info(interface_list: &str) -> Result<Option<LinkInfo>> {
+    let network = match parse_netsh_interfaces(interface_list)? {
+        Some(network) => network,
+        None => return Ok(None),
+    };
+    let mut info = LinkInfo {
+        network,
+        ..Default::default()
+    };
+    for line in interface_list.lines() {
+        let index = match line.find(':') {
+            Some(index) => index,
+            None => continue,
+        };
+        let (key, value) = (line[..index].trim(), &line[index + 1..]);
+
+        match key {
+            "Transmit rate (Mbps)" => info.tx_bitrate_mbps = parse_bitrate(value),
+            "Receive rate (Mbps)" => info.rx_bitrate_mbps = parse_bitrate(value),
+            _ => (),
+        }
+    }
+
+    Ok(Some(info))
+}
+
+/// Converts a signal quality such as `74%` to dBm.
+fn parse_signal_percent(line: &str, percent: &str, bounds: SignalBounds) -> Result<i32> {
+    let percent: u8 = percent
+        .trim()
+        .replace("%", "")
+        .parse()
+        .map_err(|err| Error::Parse {
+            line: line.to_string(),
+            reason: format!("signal isn't a percentage: {}", err),
+        })?;
+    Ok(bounds.to_dbm(percent))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,40 +318,276 @@ mod tests {
             Wifi {
                 mac: "ab:cd:ef:01:23:45".to_string(),
                 ssid: "Vodafone Hotspot".to_string(),
+                ssid_raw: b"Vodafone Hotspot".to_vec(),
                 channel: "6".to_string(),
                 signal_level: "-92".to_string(),
                 security: "Open".to_string(),
+                radio_type: Some("802.11n".to_string()),
+                encryption: Some("None".to_string()),
+                ..Default::default()
             },
             Wifi {
                 mac: "ab:cd:ef:01:23:45".to_string(),
                 ssid: "Vodafone Hotspot".to_string(),
+                ssid_raw: b"Vodafone Hotspot".to_vec(),
                 channel: "6".to_string(),
                 signal_level: "-73".to_string(),
                 security: "Open".to_string(),
+                radio_type: Some("802.11n".to_string()),
+                encryption: Some("None".to_string()),
+                ..Default::default()
             },
             Wifi {
                 mac: "ab:cd:ef:01:23:45".to_string(),
                 ssid: "EdaBox".to_string(),
+                ssid_raw: b"EdaBox".to_vec(),
                 channel: "11".to_string(),
                 signal_level: "-82".to_string(),
                 security: "WPA2-Personal".to_string(),
+                radio_type: Some("802.11n".to_string()),
+                encryption: Some("CCMP".to_string()),
+                ..Default::default()
             },
             Wifi {
                 mac: "ab:cd:ef:01:23:45".to_string(),
                 ssid: "FRITZ!Box 2345 Cable".to_string(),
+                ssid_raw: b"FRITZ!Box 2345 Cable".to_vec(),
                 channel: "1".to_string(),
                 signal_level: "-50".to_string(),
                 security: "WPA2-Personal".to_string(),
+                radio_type: Some("802.11n".to_string()),
+                encryption: Some("CCMP".to_string()),
+                ..Default::default()
             },
         ];
 
         // Load test fixtures
         let fixture = fs::read_to_string("tests/fixtures/netsh/netsh01_windows81.txt").unwrap();
 
-        let result = parse_netsh(&fixture).unwrap();
+        let result = parse_netsh(&fixture, SignalBounds::default()).unwrap();
         assert_eq!(expected[0], result[0]);
         assert_eq!(expected[1], result[1]);
         assert_eq!(expected[2], result[2]);
         assert_eq!(expected[3], result[3]);
     }
+
+    #[test]
+    fn should_parse_netsh_radio_type() {
+        use std::fs;
+
+        let fixture = fs::read_to_string("tests/fixtures/netsh/netsh02_windows10.txt").unwrap();
+
+        let result = parse_netsh(&fixture, SignalBounds::default()).unwrap();
+        let radio_types: Vec<_> = result.iter().map(|wifi| wifi.radio_type.clone()).collect();
+        assert_eq!(
+            vec![
+                Some("802.11ac".to_string()),
+                Some("802.11ax".to_string()),
+                None
+            ],
+            radio_types
+        );
+    }
+
+    #[test]
+    fn should_parse_netsh_encryption() {
+        use std::fs;
+
+        let fixture = fs::read_to_string("tests/fixtures/netsh/netsh07_windows10.txt").unwrap();
+
+        let result = parse_netsh(&fixture, SignalBounds::default()).unwrap();
+        // same authentication, different ciphers
+        assert_eq!(result[0].security, result[1].security);
+        let encryptions: Vec<_> = result
+            .iter()
+            .map(|wifi| wifi.encryption.as_deref())
+            .collect();
+        assert_eq!(vec![Some("CCMP"), Some("TKIP"), Some("None")], encryptions);
+    }
+
+    #[test]
+    fn should_skip_netsh_header() {
+        use std::fs;
+
+        let fixture = fs::read_to_string("tests/fixtures/netsh/netsh03_windows10.txt").unwrap();
+
+        let result = parse_netsh(&fixture, SignalBounds::default()).unwrap();
+        assert_eq!(1, result.len());
+        assert_eq!("10:20:30:40:50:60", result[0].mac);
+    }
+
+    #[test]
+    fn should_parse_netsh_hidden_network() {
+        use std::fs;
+
+        let fixture = fs::read_to_string("tests/fixtures/netsh/netsh04_windows10.txt").unwrap();
+
+        let result = parse_netsh(&fixture, SignalBounds::default()).unwrap();
+        assert_eq!(2, result.len());
+        assert!(!result[0].is_hidden());
+        assert!(result[1].is_hidden());
+        assert_eq!("70:80:90:a0:b0:c0", result[1].mac);
+        assert_eq!("6", result[1].channel);
+
+        let trimmed = fixture.replace("SSID 2 : \n", "SSID 2 :\n");
+        let result = parse_netsh(&trimmed, SignalBounds::default()).unwrap();
+        assert!(result[1].is_hidden());
+    }
+
+    #[test]
+    fn should_parse_netsh_ssid_with_colon() {
+        let network_list = "\nSSID 1 : Cafe: Guest\n    BSSID 1                 : ab:cd:ef:01:23:45\n         Signal             : 50%\n         Channel            : 1\n";
+
+        let result = parse_netsh(network_list, SignalBounds::default()).unwrap();
+        assert_eq!("Cafe: Guest", result[0].ssid);
+    }
+
+    #[test]
+    fn should_report_unparseable_signal() {
+        let network_list = "\nSSID 1 : EdaBox\n    BSSID 1                 : ab:cd:ef:01:23:45\n         Signal             : strong\n";
+
+        assert_eq!(
+            Error::Parse {
+                line: "         Signal             : strong".to_string(),
+                reason: "signal isn't a percentage: invalid digit found in string".to_string(),
+            },
+            parse_netsh(network_list, SignalBounds::default()).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn should_parse_netsh_interfaces() {
+        use std::fs;
+
+        let expected = Wifi {
+            mac: "ab:cd:ef:01:23:45".to_string(),
+            ssid: "EdaBox".to_string(),
+            ssid_raw: b"EdaBox".to_vec(),
+            channel: "11".to_string(),
+            signal_level: "-63".to_string(),
+            security: "WPA2-Personal".to_string(),
+            radio_type: Some("802.11n".to_string()),
+            ..Default::default()
+        };
+
+        let fixture =
+            fs::read_to_string("tests/fixtures/netsh/netsh_interfaces01_windows10.txt").unwrap();
+
+        assert_eq!(Some(expected), parse_netsh_interfaces(&fixture).unwrap());
+    }
+
+    #[test]
+    fn should_parse_netsh_link_info() {
+        use std::fs;
+
+        let fixture =
+            fs::read_to_string("tests/fixtures/netsh/netsh_interfaces01_windows10.txt").unwrap();
+
+        let info = parse_netsh_link_info(&fixture).unwrap().unwrap();
+        assert_eq!("EdaBox", info.network.ssid);
+        assert_eq!(Some(144.4), info.tx_bitrate_mbps);
+        assert_eq!(Some(144.4), info.rx_bitrate_mbps);
+    }
+
+    #[test]
+    fn should_parse_netsh_interfaces_not_connected() {
+        use std::fs;
+
+        let fixture =
+            fs::read_to_string("tests/fixtures/netsh/netsh_interfaces02_windows10.txt").unwrap();
+
+        assert_eq!(None, parse_netsh_interfaces(&fixture).unwrap());
+    }
+
+    #[test]
+    fn should_count_netsh_interfaces() {
+        use std::fs;
+
+        let fixture =
+            fs::read_to_string("tests/fixtures/netsh/netsh_interfaces02_windows10.txt").unwrap();
+
+        assert_eq!(1, parse_netsh_interface_count(&fixture));
+        assert_eq!(
+            0,
+            parse_netsh_interface_count("There is no wireless interface on the system.\n")
+        );
+    }
+
+    #[test]
+    fn should_parse_netsh_interface_list() {
+        use std::fs;
+
+        let fixture =
+            fs::read_to_string("tests/fixtures/netsh/netsh_interfaces01_windows10.txt").unwrap();
+
+        assert_eq!(
+            vec![Interface {
+                name: "Wi-Fi".to_string(),
+                connected: true,
+                ssid: Some("EdaBox".to_string()),
+                bssid: Some("ab:cd:ef:01:23:45".to_string()),
+                radio_type: Some("802.11n".to_string()),
+            }],
+            parse_netsh_interface_list(&fixture)
+        );
+    }
+
+    #[test]
+    fn should_parse_netsh_band() {
+        use std::fs;
+
+        let fixture = fs::read_to_string("tests/fixtures/netsh/netsh05_windows11.txt").unwrap();
+
+        let result = parse_netsh(&fixture, SignalBounds::default()).unwrap();
+        assert_eq!(Some(5180), result[0].frequency);
+        assert_eq!(Some(6135), result[1].frequency);
+        assert_eq!(
+            Some(Band::SixGhz),
+            result[1].channel_info().map(|channel| channel.band)
+        );
+        assert_eq!(Some(2412), result[2].frequency);
+        // older versions of Windows don't list the band
+        assert_eq!(None, result[3].frequency);
+    }
+
+    #[test]
+    fn should_list_scan_commands() {
+        assert_eq!(
+            vec!["netsh.exe wlan show networks mode=Bssid"],
+            scan_commands(&ScanOptions::default())
+        );
+    }
+
+    #[test]
+    fn should_not_take_channel_utilization_as_channel() {
+        use std::fs;
+
+        let fixture = fs::read_to_string("tests/fixtures/netsh/netsh08_windows11.txt").unwrap();
+
+        let result = parse_netsh(&fixture, SignalBounds::default()).unwrap();
+        assert_eq!(2, result.len());
+        assert_eq!("36", result[0].channel);
+        assert_eq!(Some(5180), result[0].frequency);
+        assert_eq!("-60", result[0].signal_level);
+        assert_eq!("6", result[1].channel);
+        assert_eq!(Some(2437), result[1].frequency);
+    }
+
+    #[test]
+    fn should_parse_netsh_bssid_without_channel() {
+        use std::fs;
+
+        let fixture = fs::read_to_string("tests/fixtures/netsh/netsh06_windows10.txt").unwrap();
+
+        let result = parse_netsh(&fixture, SignalBounds::default()).unwrap();
+        assert_eq!(3, result.len());
+        assert_eq!("10:20:30:40:50:60", result[0].mac);
+        assert_eq!("", result[0].channel);
+        assert_eq!("-55", result[0].signal_level);
+        // the following BSSIDs keep their own channels
+        assert_eq!("10:20:30:40:50:61", result[1].mac);
+        assert_eq!("1", result[1].channel);
+        assert_eq!("-65", result[1].signal_level);
+        assert_eq!("11", result[2].channel);
+    }
 }
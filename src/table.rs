@@ -0,0 +1,82 @@
+use crate::Wifi;
+
+const HEADERS: [&str; 5] = ["MAC", "SSID", "CHANNEL", "SIGNAL", "SECURITY"];
+
+/// Formats hotspots as a table with a header row, one hotspot per line, as printed by
+/// the `wifiscanner` binary.
+///
+/// Columns are as wide as their widest value, so long SSIDs push the following columns
+/// along rather than being cut off.
+pub fn format_table(wifis: &[Wifi]) -> String {
+    let rows: Vec<[&str; 5]> = wifis
+        .iter()
+        .map(|wifi| {
+            [
+                wifi.mac.as_str(),
+                wifi.ssid.as_str(),
+                wifi.channel.as_str(),
+                wifi.signal_level.as_str(),
+                wifi.security.as_str(),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADERS.map(|header| header.chars().count());
+    for row in &rows {
+        for (width, value) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    for row in std::iter::once(&HEADERS).chain(rows.iter()) {
+        let line = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(value, &width)| format!("{:width$}", value, width = width))
+            .collect::<Vec<_>>()
+            .join(" ");
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_format_table() {
+        let wifis = [
+            Wifi {
+                mac: "11:22:33:44:55:66".to_string(),
+                ssid: "hello".to_string(),
+                channel: "10".to_string(),
+                signal_level: "-67".to_string(),
+                security: "WPA2-Personal".to_string(),
+                ..Default::default()
+            },
+            Wifi {
+                mac: "66:77:88:99:aa:bb".to_string(),
+                ssid: "hello-world-foo-bar".to_string(),
+                channel: "149".to_string(),
+                signal_level: "-89.00".to_string(),
+                security: "Open".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(
+            "MAC               SSID                CHANNEL SIGNAL SECURITY\n\
+             11:22:33:44:55:66 hello               10      -67    WPA2-Personal\n\
+             66:77:88:99:aa:bb hello-world-foo-bar 149     -89.00 Open\n",
+            format_table(&wifis)
+        );
+    }
+
+    #[test]
+    fn should_format_empty_table() {
+        assert_eq!("MAC SSID CHANNEL SIGNAL SECURITY\n", format_table(&[]));
+    }
+}
@@ -0,0 +1,126 @@
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
+
+use crate::{Result, ScanOptions, Wifi};
+
+/// Remembers the last scan, so scans requested too soon after it can return its results
+/// instead, see `ScanOptions::min_interval`.
+pub(crate) struct Throttle {
+    /// the `ScanOptions::scan_key` of the options the scan was made with
+    last_scan: Mutex<Option<(ScanOptions, Instant, Vec<Wifi>)>>,
+}
+
+/// Shared by every call to `scan_with_options`.
+pub(crate) static THROTTLE: Throttle = Throttle::new();
+
+impl Throttle {
+    pub(crate) const fn new() -> Throttle {
+        Throttle {
+            last_scan: Mutex::new(None),
+        }
+    }
+
+    /// Runs `scan` unless the last successful scan with the same options for the scan
+    /// itself finished less than `options.min_interval` ago, in which case its results
+    /// are returned. Scans are only remembered with a `min_interval`, failed ones never.
+    ///
+    /// The lock isn't held while scanning, so callers racing each other may all scan.
+    pub(crate) fn scan<F>(&self, options: &ScanOptions, scan: F) -> Result<Vec<Wifi>>
+    where
+        F: FnOnce() -> Result<Vec<Wifi>>,
+    {
+        let min_interval = match options.min_interval {
+            Some(min_interval) => min_interval,
+            None => return scan(),
+        };
+        let key = options.scan_key();
+        {
+            let last_scan = self
+                .last_scan
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if let Some((last_key, time, wifis)) = last_scan.as_ref() {
+                if *last_key == key && time.elapsed() < min_interval {
+                    return Ok(wifis.clone());
+                }
+            }
+        }
+
+        let wifis = scan()?;
+        *self
+            .last_scan
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some((key, Instant::now(), wifis.clone()));
+        Ok(wifis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Band, SortKey};
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    #[test]
+    fn should_return_last_scan_within_min_interval() {
+        let throttle = Throttle::new();
+        let scans = Cell::new(0);
+        let scan = || {
+            scans.set(scans.get() + 1);
+            Ok(vec![Wifi {
+                mac: format!("00:00:00:00:00:0{}", scans.get()),
+                ..Default::default()
+            }])
+        };
+        let options = ScanOptions::new().min_interval(Duration::from_secs(60));
+
+        let first = throttle.scan(&options, scan).unwrap();
+        assert_eq!(first, throttle.scan(&options, scan).unwrap());
+        assert_eq!(1, scans.get());
+
+        // sorting and limiting apply to the hotspots found, not to the scan
+        let sorted = options.clone().sort_by(SortKey::Signal).limit(1);
+        assert_eq!(first, throttle.scan(&sorted, scan).unwrap());
+        assert_eq!(1, scans.get());
+
+        // without a minimum interval the radio is always scanned
+        assert_ne!(first, throttle.scan(&ScanOptions::new(), scan).unwrap());
+        assert_eq!(2, scans.get());
+
+        let zero = ScanOptions::new().min_interval(Duration::ZERO);
+        assert_ne!(first, throttle.scan(&zero, scan).unwrap());
+        assert_eq!(3, scans.get());
+    }
+
+    #[test]
+    fn should_not_reuse_scans_with_other_options() {
+        let throttle = Throttle::new();
+        let scans = Cell::new(0);
+        let scan = || {
+            scans.set(scans.get() + 1);
+            Ok(vec![])
+        };
+        let options = ScanOptions::new().min_interval(Duration::from_secs(60));
+
+        let german = options.clone().set_reg_domain("DE");
+
+        throttle
+            .scan(&options.clone().frequencies(vec![2412]), scan)
+            .unwrap();
+        throttle
+            .scan(&options.clone().band(Band::FiveGhz), scan)
+            .unwrap();
+        throttle.scan(&options.clone().passes(3), scan).unwrap();
+        throttle.scan(&german, scan).unwrap();
+        assert_eq!(4, scans.get());
+        throttle.scan(&german, scan).unwrap();
+        assert_eq!(4, scans.get());
+
+        // scans without a minimum interval aren't remembered, so don't replace it
+        throttle.scan(&ScanOptions::new(), scan).unwrap();
+        assert_eq!(5, scans.get());
+        throttle.scan(&german, scan).unwrap();
+        assert_eq!(5, scans.get());
+    }
+}
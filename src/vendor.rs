@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use crate::security::oui;
+use crate::Wifi;
+
+/// Vendor prefixes (OUIs) of common access point and device manufacturers. This is a
+/// small subset of the IEEE registry, hotspots from other vendors are reported by
+/// their prefix.
+const VENDORS: &[(&str, &str)] = &[
+    ("00:00:0c", "Cisco"),
+    ("00:0b:86", "Aruba"),
+    ("00:0c:29", "VMware"),
+    ("00:14:6c", "Netgear"),
+    ("00:17:88", "Philips"),
+    ("00:18:0a", "Cisco Meraki"),
+    ("00:1a:1e", "Aruba"),
+    ("00:1b:63", "Apple"),
+    ("00:26:bb", "Apple"),
+    ("00:50:56", "VMware"),
+    ("04:18:d6", "Ubiquiti"),
+    ("08:00:27", "VirtualBox"),
+    ("24:a4:3c", "Ubiquiti"),
+    ("3c:5a:b4", "Google"),
+    ("50:c7:bf", "TP-Link"),
+    ("b8:27:eb", "Raspberry Pi"),
+    ("dc:a6:32", "Raspberry Pi"),
+];
+
+/// Key used for mac addresses with the locally administered bit set, which are
+/// randomized rather than assigned to a vendor.
+const LOCALLY_ADMINISTERED: &str = "Locally administered";
+
+impl Wifi {
+    /// Returns the manufacturer of the hotspot, looked up from the first three octets
+    /// (OUI) of its mac address, or `None` if the vendor isn't known.
+    ///
+    /// Only a small built-in list of common vendors is known.
+    pub fn vendor(&self) -> Option<&'static str> {
+        let oui = oui(&self.mac);
+        VENDORS
+            .iter()
+            .find(|(prefix, _)| *prefix == oui)
+            .map(|(_, vendor)| *vendor)
+    }
+}
+
+/// Groups hotspots by manufacturer, see `Wifi::vendor`.
+///
+/// Hotspots from vendors that aren't known are grouped by their OUI (e.g. `00:35:1a`),
+/// and those with a randomized mac address under `Locally administered`.
+pub fn group_by_vendor(wifis: &[Wifi]) -> BTreeMap<String, Vec<Wifi>> {
+    let mut vendors: BTreeMap<String, Vec<Wifi>> = BTreeMap::new();
+    for wifi in wifis {
+        let oui = oui(&wifi.mac);
+        let key = match wifi.vendor() {
+            Some(vendor) => vendor.to_string(),
+            None if is_locally_administered(&oui) => LOCALLY_ADMINISTERED.to_string(),
+            None => oui,
+        };
+        vendors.entry(key).or_default().push(wifi.clone());
+    }
+    vendors
+}
+
+/// Returns true if the second least significant bit of the first octet is set.
+fn is_locally_administered(oui: &str) -> bool {
+    u8::from_str_radix(&oui[..oui.find(':').unwrap_or(oui.len())], 16)
+        .is_ok_and(|octet| octet & 0x02 != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wifi(mac: &str) -> Wifi {
+        Wifi {
+            mac: mac.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_look_up_vendor() {
+        assert_eq!(Some("Ubiquiti"), wifi("24:A4:3C:01:02:03").vendor());
+        assert_eq!(Some("Cisco"), wifi("0:0:c:1:2:3").vendor());
+        assert_eq!(None, wifi("00:35:1a:90:56:03").vendor());
+    }
+
+    #[test]
+    fn should_group_by_vendor() {
+        let wifis = [
+            wifi("24:a4:3c:01:02:03"),
+            wifi("00:35:1a:90:56:03"),
+            wifi("04:18:d6:01:02:03"),
+            wifi("da:a1:19:01:02:03"),
+            wifi("0:35:1a:90:56:4"),
+        ];
+
+        let groups = group_by_vendor(&wifis);
+        let macs =
+            |key: &str| -> Vec<&str> { groups[key].iter().map(|wifi| wifi.mac.as_str()).collect() };
+        assert_eq!(3, groups.len());
+        assert_eq!(
+            vec!["24:a4:3c:01:02:03", "04:18:d6:01:02:03"],
+            macs("Ubiquiti")
+        );
+        assert_eq!(
+            vec!["00:35:1a:90:56:03", "0:35:1a:90:56:4"],
+            macs("00:35:1a")
+        );
+        assert_eq!(vec!["da:a1:19:01:02:03"], macs(LOCALLY_ADMINISTERED));
+    }
+}